@@ -0,0 +1,231 @@
+use std::{
+    fmt::Debug,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use advent_of_code::{
+    get_input,
+    grid::{E, N, S, Vector, W, Z},
+    intcode::{self, State},
+    search,
+    solutions::{self, Solution},
+};
+use anyhow::{Context, Result, bail};
+
+mod reference;
+mod synthetic;
+mod unbounded_vec;
+
+/// The 2019 days whose solutions run Intcode.
+const DAYS: [u8; 12] = [2, 5, 7, 9, 11, 13, 15, 17, 19, 21, 23, 25];
+
+/// What the workloads need from an interpreter, so that each can run on both. The solutions only
+/// run on the current interpreter, so the workloads redo their Intcode parts generically.
+trait Intcode: Clone {
+    fn state(&mut self) -> State;
+    fn input(&mut self, value: i64);
+    fn output(&mut self) -> i64;
+    fn get(&self, addr: usize) -> i64;
+    fn set(&mut self, addr: usize, value: i64);
+}
+
+impl Intcode for intcode::VM {
+    fn state(&mut self) -> State {
+        self.state()
+    }
+    fn input(&mut self, value: i64) {
+        self.input(value);
+    }
+    fn output(&mut self) -> i64 {
+        self.output()
+    }
+    fn get(&self, addr: usize) -> i64 {
+        self.mem[addr]
+    }
+    fn set(&mut self, addr: usize, value: i64) {
+        self.mem[addr] = value;
+    }
+}
+
+impl Intcode for reference::VM {
+    fn state(&mut self) -> State {
+        self.state()
+    }
+    fn input(&mut self, value: i64) {
+        self.input(value);
+    }
+    fn output(&mut self) -> i64 {
+        self.output()
+    }
+    fn get(&self, addr: usize) -> i64 {
+        self.mem[addr]
+    }
+    fn set(&mut self, addr: usize, value: i64) {
+        self.mem[addr] = value;
+    }
+}
+
+/// Day 2 part 2: run the program from scratch for every noun and verb.
+fn day02<T: Intcode>(vm: &T) -> i64 {
+    (0..100)
+        .flat_map(|noun| (0..100).map(move |verb| (noun, verb)))
+        .find(|&(noun, verb)| {
+            let mut vm = vm.clone();
+            vm.set(1, noun);
+            vm.set(2, verb);
+            assert_eq!(vm.state(), State::Halt);
+            vm.get(0) == 19_690_720
+        })
+        .map(|(noun, verb)| 100 * noun + verb)
+        .unwrap()
+}
+
+/// Day 9 part 2: one long computation.
+fn day09<T: Intcode>(vm: &T) -> i64 {
+    let mut vm = vm.clone();
+    vm.input(2);
+    vm.output()
+}
+
+/// The synthetic stand-in for day 9.
+fn fibonacci<T: Intcode>(vm: &T) -> i64 {
+    let mut vm = vm.clone();
+    vm.input(27);
+    vm.output()
+}
+
+/// Day 13 part 1: draw the screen.
+fn day13<T: Intcode>(vm: &T) -> i64 {
+    let mut vm = vm.clone();
+    let mut blocks = 0;
+    while vm.state() == State::Output {
+        vm.output();
+        vm.output();
+        blocks += i64::from(vm.output() == 2);
+    }
+    blocks
+}
+
+/// Day 15 part 1: search the maze breadth first for the oxygen system, cloning the droid's VM for
+/// every move.
+fn day15<T: Intcode>(vm: &T) -> i64 {
+    search::breadth_first(
+        (vm.clone(), Z, 0, false),
+        |(vm, pos, steps, _), push| {
+            for (i, dir) in (1..).zip([N, S, W, E]) {
+                let mut vm = vm.clone();
+                vm.input(i);
+                match vm.output() {
+                    0 => {}
+                    status => push((vm, pos + dir, steps + 1, status == 2)),
+                }
+            }
+        },
+        search::hash_filter(|&(_, pos, _, _): &(T, Vector, i64, bool)| pos),
+    )
+    .find(|&(_, _, _, found)| found)
+    .unwrap()
+    .2
+}
+
+/// Day 19 part 1: a fresh VM for every point in a 50 by 50 scan.
+fn day19<T: Intcode>(vm: &T) -> i64 {
+    let mut affected = 0;
+    for y in 0..50 {
+        for x in 0..50 {
+            let mut vm = vm.clone();
+            vm.input(x);
+            vm.input(y);
+            affected += vm.output();
+        }
+    }
+    affected
+}
+
+fn time<T: Debug>(mut f: impl FnMut() -> T) -> (T, Duration) {
+    const RUNS: u32 = 5;
+    let now = Instant::now();
+    let mut res = f();
+    for _ in 1..RUNS {
+        res = f();
+    }
+    (res, now.elapsed() / RUNS)
+}
+
+/// The puzzle input for a 2019 day, failing with instructions rather than a panic if it isn't
+/// saved and can't be fetched.
+fn input(day: u8) -> Result<String> {
+    let path = format!("input/2019/{day:0>2}");
+    if !Path::new(&path).exists() && !Path::new(".session").exists() {
+        bail!(
+            "no input for 2019 day {day}: save it to {path}, or put a session cookie in .session"
+        );
+    }
+    Ok(get_input(2019, day))
+}
+
+/// Time a workload on both interpreters, checking that they agree, and print the times.
+fn race(
+    name: &str,
+    prog: &str,
+    workload: fn(&reference::VM) -> i64,
+    current: fn(&intcode::VM) -> i64,
+) -> i64 {
+    let (expected, reference_time) = time(|| workload(&reference::VM::new(prog)));
+    let (actual, current_time) = time(|| current(&intcode::VM::new(prog)));
+    assert_eq!(actual, expected);
+    println!(
+        "{name:<10}{reference_time:>10.2?}   {current_time:>10.2?}   {:>5.2}x",
+        reference_time.as_secs_f64() / current_time.as_secs_f64()
+    );
+    actual
+}
+
+/// Race the interpreters on a day's puzzle input, also checking that they agree with the
+/// solution's answer.
+fn compare(
+    day: u8,
+    answer: fn(&str) -> String,
+    workload: fn(&reference::VM) -> i64,
+    current: fn(&intcode::VM) -> i64,
+) -> Result<()> {
+    let input = input(day)?;
+    let prog = input.trim_end_matches('\n');
+    let actual = race(&format!("2019 {day:0>2}"), prog, workload, current);
+    assert_eq!(actual.to_string(), answer(&input));
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    println!("synthetic  reference      current   speedup");
+    race("like 09", &synthetic::fibonacci(), fibonacci, fibonacci);
+    race("like 15", &synthetic::maze(), day15, day15);
+    race("like 19", &synthetic::beam(), day19, day19);
+    println!();
+
+    let solutions = solutions::build();
+    let solution = |day: u8| -> &Solution { &solutions[&2019][&day] };
+
+    println!("day       part 1       part 2");
+    for day in DAYS {
+        let input = input(day)?;
+        let mut line = format!("2019 {day:0>2}");
+        for part in [solution(day).part1, solution(day).part2] {
+            let time = part.map(|part| time(|| part(&input)).1);
+            line += &time.map_or(String::new(), |time| format!("   {time:>10.2?}"));
+        }
+        println!("{line}");
+    }
+
+    println!();
+    println!("day        reference      current   speedup");
+    let part1 = |day: u8| solution(day).part1.context("no part 1");
+    let part2 = |day: u8| solution(day).part2.context("no part 2");
+    compare(2, part2(2)?, day02, day02)?;
+    compare(9, part2(9)?, day09, day09)?;
+    compare(13, part1(13)?, day13, day13)?;
+    compare(15, part1(15)?, day15, day15)?;
+    compare(19, part1(19)?, day19, day19)?;
+    Ok(())
+}
@@ -0,0 +1,155 @@
+//! Programs written in Intcode assembly with the same shape of workload as the 2019 days we
+//! benchmark, so that the interpreters can be compared without anyone's puzzle input.
+
+use advent_of_code::intcode::asm;
+
+/// Like day 9: one long computation, calling recursively through a stack addressed relative to
+/// the base. Inputs n and outputs the nth Fibonacci number.
+pub fn fibonacci() -> String {
+    asm::assemble(
+        "
+            arb #stack
+            in [r+1]
+            add #done, #0, [r]
+            jt #1, #fib
+        done:
+            out [r+2]
+            hlt
+
+        ; Each frame holds the return address, the argument, the result, and a temporary.
+        fib:
+            lt [r+1], #2, [r+3]
+            jf [r+3], #recurse
+            add [r+1], #0, [r+2]
+            jt #1, [r]
+        recurse:
+            arb #4
+            add [r-3], #-1, [r+1]
+            add #back1, #0, [r]
+            jt #1, #fib
+        back1:
+            add [r+2], #0, [r-1]
+            add [r-3], #-2, [r+1]
+            add #back2, #0, [r]
+            jt #1, #fib
+        back2:
+            add [r+2], [r-1], [r-2]
+            arb #-4
+            jt #1, [r]
+
+        stack:
+            data 0
+        ",
+    )
+    .unwrap()
+}
+
+/// Like day 15: a droid in a maze, which takes a move (1 to 4 for north, south, west, and east)
+/// and outputs 0 if it hit a wall, 1 if it moved, and 2 if it moved onto the oxygen system. The
+/// maze is a grid of pillars, so there are plenty of routes to search.
+pub fn maze() -> String {
+    const SIZE: usize = 41;
+    let cells: Vec<&str> = (0..SIZE * SIZE)
+        .map(|i| {
+            let (x, y) = (i % SIZE, i / SIZE);
+            if x == 0 || y == 0 || x == SIZE - 1 || y == SIZE - 1 || (x % 2 == 0 && y % 2 == 0) {
+                "0"
+            } else if (x, y) == (SIZE - 2, SIZE - 2) {
+                "2"
+            } else {
+                "1"
+            }
+        })
+        .collect();
+    asm::assemble(&format!(
+        "
+        loop:
+            in [dir]
+            add [x], #0, [nx]
+            add [y], #0, [ny]
+            eq [dir], #1, [t]
+            jf [t], #south
+            add [ny], #-1, [ny]
+        south:
+            eq [dir], #2, [t]
+            jf [t], #west
+            add [ny], #1, [ny]
+        west:
+            eq [dir], #3, [t]
+            jf [t], #east
+            add [nx], #-1, [nx]
+        east:
+            eq [dir], #4, [t]
+            jf [t], #look
+            add [nx], #1, [nx]
+
+        ; Read the cell by moving the base to it and back.
+        look:
+            mul [ny], #{SIZE}, [i]
+            add [i], [nx], [i]
+            arb [i]
+            add [r+maze], #0, [cell]
+            mul [i], #-1, [i]
+            arb [i]
+            jf [cell], #report
+            add [nx], #0, [x]
+            add [ny], #0, [y]
+        report:
+            out [cell]
+            jt #1, #loop
+
+        dir: data 0
+        x: data 1
+        y: data 1
+        nx: data 0
+        ny: data 0
+        i: data 0
+        t: data 0
+        cell: data 0
+        maze: data {}
+        ",
+        cells.join(", ")
+    ))
+    .unwrap()
+}
+
+/// Like day 19: takes x and y, and outputs whether the point is in a beam between two lines from
+/// the origin. Multiplying x and y by repeated addition stands in for the real program's busywork.
+pub fn beam() -> String {
+    asm::assemble(
+        "
+            in [x]
+            in [y]
+            add [y], #0, [i]
+        times:
+            jf [i], #check
+            add [product], [x], [product]
+            add [i], #-1, [i]
+            jt #1, #times
+
+        check:
+            mul [x], #5, [a]
+            mul [y], #4, [b]
+            lt [a], [b], [t]
+            jt [t], #outside
+            mul [y], #3, [a]
+            mul [x], #2, [b]
+            lt [a], [b], [t]
+            jt [t], #outside
+            out #1
+            hlt
+        outside:
+            out #0
+            hlt
+
+        x: data 0
+        y: data 0
+        i: data 0
+        product: data 0
+        a: data 0
+        b: data 0
+        t: data 0
+        ",
+    )
+    .unwrap()
+}
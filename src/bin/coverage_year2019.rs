@@ -0,0 +1,44 @@
+use std::env;
+
+use advent_of_code::{
+    get_input,
+    intcode::{State, VM, parse},
+};
+use anyhow::{Context, Result};
+
+/// Run a 2019 Intcode program on the given inputs, then print its disassembly annotated with how
+/// often each instruction ran and each address was used, followed by the hottest instructions.
+fn main() -> Result<()> {
+    let mut args = env::args().skip(1);
+    let day = args
+        .next()
+        .context("usage: coverage_year2019 <day> [input...]")?
+        .parse()?;
+    let inputs = args
+        .map(|arg| arg.parse())
+        .collect::<Result<Vec<i64>, _>>()?;
+    let prog = get_input(2019, day);
+    let prog = prog.trim_end_matches('\n');
+
+    let mut vm = VM::new(prog);
+    vm.trace();
+    let mut inputs = inputs.into_iter();
+    loop {
+        match vm.state() {
+            State::Input => match inputs.next() {
+                Some(input) => vm.input(input),
+                None => break,
+            },
+            State::Output => println!("output {}", vm.output()),
+            State::Halt => break,
+        }
+    }
+
+    let coverage = vm.coverage().unwrap();
+    print!("{}", coverage.report(&parse(prog)));
+    println!("\nhottest instructions:");
+    for (addr, count) in coverage.hottest(10) {
+        println!("{addr:>6} {count:>10}");
+    }
+    Ok(())
+}
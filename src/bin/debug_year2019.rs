@@ -0,0 +1,20 @@
+use std::{env, io};
+
+use advent_of_code::{
+    get_input,
+    intcode::{
+        VM,
+        debug::{Debugger, repl},
+    },
+};
+use anyhow::{Context, Result};
+
+fn main() -> Result<()> {
+    let day = env::args()
+        .nth(1)
+        .context("usage: debug_year2019 <day>")?
+        .parse()?;
+    let vm = VM::new(get_input(2019, day).trim_end_matches('\n'));
+    repl(&mut Debugger::new(vm), io::stdin().lock(), io::stdout())?;
+    Ok(())
+}
@@ -0,0 +1,19 @@
+use std::env;
+
+use advent_of_code::{
+    get_input,
+    intcode::{asm, parse},
+};
+use anyhow::{Context, Result};
+
+fn main() -> Result<()> {
+    let day = env::args()
+        .nth(1)
+        .context("usage: disassemble_year2019 <day>")?
+        .parse()?;
+    print!(
+        "{}",
+        asm::disassemble(&parse(get_input(2019, day).trim_end_matches('\n')))
+    );
+    Ok(())
+}
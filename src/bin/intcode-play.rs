@@ -0,0 +1,467 @@
+use std::{
+    collections::VecDeque,
+    env,
+    fs::{self, File},
+    io::{self, IsTerminal, Read, StdinLock, Write},
+    process::{self, Command, Stdio},
+};
+
+use advent_of_code::{
+    get_input,
+    grid::Vector,
+    intcode::{State, VM, snapshot::Snapshot},
+    render::{Cell, Terminal},
+    solutions::year2019::day13,
+};
+use anyhow::{Context, Result, bail};
+
+const USAGE: &str = "\
+usage: intcode-play [options] <program file | year/day>
+
+Input is sent as lines of ASCII text once the program has printed some, and as numbers separated
+by spaces before then. Days that need it are set up automatically: 2019/13 is drawn as tiles with
+quarters in, and 2019/17 wakes the robot up.
+
+options:
+    --script <file>       enter the lines of a file before reading from the keyboard
+    --transcript <file>   record everything shown and entered to a file
+    --tiles               draw output as (x, y, tile) triples, with (-1, 0, n) as the score, and
+                          take a, d, or nothing to move the joystick
+    --set <addr>=<value>  write to memory before starting
+
+commands:
+    :save <file>          save the session, as JSON if the file ends in .json
+    :load <file>          carry on from a saved session
+    :history              show the lines entered so far
+    :quit                 stop playing
+";
+
+struct Options {
+    program: String,
+    script: Option<String>,
+    transcript: Option<String>,
+    tiles: bool,
+    pokes: Vec<(usize, i64)>,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self> {
+        let mut program = None;
+        let mut options = Options {
+            program: String::new(),
+            script: None,
+            transcript: None,
+            tiles: false,
+            pokes: Vec::new(),
+        };
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().with_context(|| format!("{arg} needs a value"));
+            match arg.as_str() {
+                "--script" => options.script = Some(value()?),
+                "--transcript" => options.transcript = Some(value()?),
+                "--tiles" => options.tiles = true,
+                "--set" => {
+                    let poke = value()?;
+                    let (addr, value) = poke.split_once('=').context("--set needs addr=value")?;
+                    options.pokes.push((addr.parse()?, value.parse()?));
+                }
+                "-h" | "--help" => {
+                    print!("{USAGE}");
+                    process::exit(0);
+                }
+                _ if arg.starts_with('-') => bail!("unknown option {arg}\n\n{USAGE}"),
+                _ if program.is_some() => bail!("more than one program given\n\n{USAGE}"),
+                _ => program = Some(arg),
+            }
+        }
+        options.program = program.with_context(|| USAGE.to_string())?;
+        Ok(options)
+    }
+
+    /// The program named by `year/day`, or else read from a file.
+    fn load_program(&mut self) -> Result<String> {
+        let puzzle = self
+            .program
+            .split_once('/')
+            .and_then(|(year, day)| Some((year.parse().ok()?, day.parse().ok()?)));
+        let Some((year, day)) = puzzle else {
+            return fs::read_to_string(&self.program)
+                .with_context(|| format!("failed to read {}", self.program));
+        };
+        match (year, day) {
+            (2019, 13) => {
+                self.tiles = true;
+                self.pokes.insert(0, (0, 2));
+            }
+            (2019, 17) => self.pokes.insert(0, (0, 2)),
+            _ => {}
+        }
+        Ok(get_input(year, day))
+    }
+}
+
+/// Puts the terminal into raw mode with `stty` for as long as it's alive, so that keys can be read
+/// as they're pressed.
+struct RawMode {
+    saved: String,
+}
+
+impl RawMode {
+    fn new() -> io::Result<Self> {
+        let saved = stty(&["-g"])?.trim().to_string();
+        stty(&["-icanon", "-echo", "min", "1"])?;
+        Ok(Self { saved })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        // Errors are ignored since there's nothing useful to do with them here.
+        let _ = stty(&[&self.saved]);
+    }
+}
+
+fn stty(args: &[&str]) -> io::Result<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other("stty failed"));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+enum Key {
+    Char(char),
+    Enter,
+    Backspace,
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+    Eof,
+    Other,
+}
+
+fn read_key(r: &mut impl Read) -> io::Result<Key> {
+    let mut byte = || -> io::Result<Option<u8>> {
+        let mut buf = [0];
+        match r.read_exact(&mut buf) {
+            Ok(()) => Ok(Some(buf[0])),
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+            Err(err) => Err(err),
+        }
+    };
+    Ok(match byte()? {
+        None | Some(0x04) => Key::Eof,
+        Some(b'\r' | b'\n') => Key::Enter,
+        Some(0x7f | 0x08) => Key::Backspace,
+        Some(0x01) => Key::Home,
+        Some(0x05) => Key::End,
+        Some(0x1b) => match (byte()?, byte()?) {
+            (Some(b'['), Some(b'A')) => Key::Up,
+            (Some(b'['), Some(b'B')) => Key::Down,
+            (Some(b'['), Some(b'C')) => Key::Right,
+            (Some(b'['), Some(b'D')) => Key::Left,
+            (Some(b'['), Some(b'H')) => Key::Home,
+            (Some(b'['), Some(b'F')) => Key::End,
+            _ => Key::Other,
+        },
+        Some(b) if b.is_ascii_graphic() || b == b' ' => Key::Char(char::from(b)),
+        Some(_) => Key::Other,
+    })
+}
+
+/// Read a line from the terminal with the usual editing keys: left and right, backspace, home and
+/// end (or ctrl-a and ctrl-e), and up and down to go through `history`. Returns `None` for ctrl-d
+/// on an empty line.
+fn edit_line(prompt: &str, history: &[String]) -> io::Result<Option<String>> {
+    let _raw = RawMode::new()?;
+    let mut stdin = io::stdin().lock();
+    let mut stdout = io::stdout();
+    let mut line: Vec<char> = Vec::new();
+    let mut cursor = 0;
+    let mut recalled = history.len();
+    loop {
+        let text: String = line.iter().collect();
+        write!(stdout, "\r\x1b[K{prompt}{text}")?;
+        if cursor < line.len() {
+            write!(stdout, "\x1b[{}D", line.len() - cursor)?;
+        }
+        stdout.flush()?;
+        match read_key(&mut stdin)? {
+            Key::Enter => {
+                writeln!(stdout)?;
+                return Ok(Some(text));
+            }
+            Key::Eof if line.is_empty() => {
+                writeln!(stdout)?;
+                return Ok(None);
+            }
+            Key::Char(c) => {
+                line.insert(cursor, c);
+                cursor += 1;
+            }
+            Key::Backspace if cursor > 0 => {
+                cursor -= 1;
+                line.remove(cursor);
+            }
+            Key::Left => cursor = cursor.saturating_sub(1),
+            Key::Right => cursor = (cursor + 1).min(line.len()),
+            Key::Home => cursor = 0,
+            Key::End => cursor = line.len(),
+            key @ (Key::Up | Key::Down) => {
+                recalled = match key {
+                    Key::Up => recalled.saturating_sub(1),
+                    _ => (recalled + 1).min(history.len()),
+                };
+                line = history
+                    .get(recalled)
+                    .map_or(Vec::new(), |line| line.chars().collect());
+                cursor = line.len();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Draws `(x, y, tile)` triples as they're output.
+struct Screen {
+    terminal: Terminal<io::Stdout>,
+    pending: Vec<i64>,
+    height: i32,
+}
+
+impl Screen {
+    fn output(&mut self, value: i64) -> Result<()> {
+        self.pending.push(value);
+        let [x, y, tile] = self.pending[..] else {
+            return Ok(());
+        };
+        self.pending.clear();
+        if (x, y) == (-1, 0) {
+            self.terminal
+                .draw_str(Vector::new(0, 0), &format!("score: {tile}\x1b[K"))?;
+            return Ok(());
+        }
+        let (x, y) = (i32::try_from(x)?, i32::try_from(y)?);
+        self.height = self.height.max(y + 1);
+        let cell = day13::tile(tile).unwrap_or_else(|| Cell::new('?'));
+        self.terminal.draw_cell(Vector::new(x, y + 1), cell)?;
+        Ok(())
+    }
+
+    /// Move below the tiles, to read input there.
+    fn prompt_row(&mut self) -> Result<()> {
+        self.terminal
+            .draw_str(Vector::new(0, self.height + 2), "\x1b[K")?;
+        Ok(())
+    }
+}
+
+struct Session {
+    vm: VM,
+    script: VecDeque<String>,
+    keyboard: Option<io::Lines<StdinLock<'static>>>,
+    history: Vec<String>,
+    transcript: Option<File>,
+    screen: Option<Screen>,
+    /// Input waiting for the VM to ask for it.
+    queued: VecDeque<i64>,
+    /// Whether the program has printed any text, so input should be text too.
+    ascii: bool,
+    line_start: bool,
+}
+
+impl Session {
+    fn run(&mut self) -> Result<()> {
+        loop {
+            match self.vm.state() {
+                State::Output => {
+                    let value = self.vm.output();
+                    self.show(value)?;
+                }
+                State::Input => {
+                    if let Some(value) = self.queued.pop_front() {
+                        self.vm.input(value);
+                        continue;
+                    }
+                    let Some(line) = self.next_line()? else {
+                        return Ok(());
+                    };
+                    if !self.enter(&line)? {
+                        return Ok(());
+                    }
+                }
+                State::Halt => return Ok(()),
+            }
+        }
+    }
+
+    fn show(&mut self, value: i64) -> Result<()> {
+        if let Some(screen) = &mut self.screen {
+            return screen.output(value);
+        }
+        let c = u8::try_from(value)
+            .ok()
+            .filter(|c| c.is_ascii_graphic() || c.is_ascii_whitespace());
+        match c {
+            Some(c) => {
+                self.ascii = true;
+                self.say(&char::from(c).to_string())
+            }
+            None if self.line_start => self.say(&format!("{value}\n")),
+            None => self.say(&format!("\n{value}\n")),
+        }
+    }
+
+    /// Show some text, and record it in the transcript.
+    fn say(&mut self, text: &str) -> Result<()> {
+        print!("{text}");
+        io::stdout().flush()?;
+        if let Some(transcript) = &mut self.transcript {
+            transcript.write_all(text.as_bytes())?;
+        }
+        if let Some(c) = text.chars().last() {
+            self.line_start = c == '\n';
+        }
+        Ok(())
+    }
+
+    /// The next line of input, from the script if there's any left, otherwise the keyboard.
+    fn next_line(&mut self) -> Result<Option<String>> {
+        const PROMPT: &str = "> ";
+        if let Some(screen) = &mut self.screen {
+            screen.prompt_row()?;
+        } else if !self.line_start {
+            self.say("\n")?;
+        }
+        let line = if let Some(line) = self.script.pop_front() {
+            println!("{PROMPT}{line}");
+            line
+        } else if let Some(keyboard) = &mut self.keyboard {
+            // Nothing echoes input that isn't from a terminal, so show it like the script.
+            let Some(line) = keyboard.next().transpose()? else {
+                return Ok(None);
+            };
+            println!("{PROMPT}{line}");
+            line
+        } else {
+            match edit_line(PROMPT, &self.history)? {
+                Some(line) => line,
+                None => return Ok(None),
+            }
+        };
+        if let Some(transcript) = &mut self.transcript {
+            writeln!(transcript, "{PROMPT}{line}")?;
+        }
+        self.line_start = true;
+        if !line.is_empty() && self.history.last() != Some(&line) {
+            self.history.push(line.clone());
+        }
+        Ok(Some(line))
+    }
+
+    /// Act on a line of input. Returns false to quit.
+    fn enter(&mut self, line: &str) -> Result<bool> {
+        if let Some(command) = line.strip_prefix(':') {
+            return self.command(command);
+        }
+        if self.screen.is_some() {
+            match line.trim() {
+                "a" => self.queued.push_back(-1),
+                "" => self.queued.push_back(0),
+                "d" => self.queued.push_back(1),
+                _ => self.say("move with a, d, or nothing\n")?,
+            }
+        } else if self.ascii {
+            if line.is_ascii() {
+                self.queued.extend(line.bytes().map(i64::from));
+                self.queued.push_back(i64::from(b'\n'));
+            } else {
+                self.say("only ASCII can be sent\n")?;
+            }
+        } else {
+            match line.split_whitespace().map(str::parse).collect() {
+                Ok(values) => self.queued = values,
+                Err(err) => self.say(&format!("{err}\n"))?,
+            }
+        }
+        Ok(true)
+    }
+
+    fn command(&mut self, command: &str) -> Result<bool> {
+        let (name, arg) = command.split_once(' ').unwrap_or((command, ""));
+        match (name, arg.trim()) {
+            ("save", path) if !path.is_empty() => match self.vm.snapshot().save(path) {
+                Ok(()) => self.say(&format!("saved to {path}\n"))?,
+                Err(err) => self.say(&format!("{err}\n"))?,
+            },
+            ("load", path) if !path.is_empty() => match Snapshot::load(path) {
+                Ok(snapshot) => {
+                    self.vm.restore(&snapshot);
+                    self.queued.clear();
+                    self.say(&format!("loaded {path}\n"))?;
+                }
+                Err(err) => self.say(&format!("{err}\n"))?,
+            },
+            ("history", _) => {
+                let history: String = self
+                    .history
+                    .iter()
+                    .map(|line| line.clone() + "\n")
+                    .collect();
+                self.say(&history)?;
+            }
+            ("quit", _) => return Ok(false),
+            _ => self.say("commands are :save, :load, :history and :quit\n")?,
+        }
+        Ok(true)
+    }
+}
+
+fn main() -> Result<()> {
+    let mut options = Options::parse(env::args().skip(1))?;
+    let program = options.load_program()?;
+    let mut vm = VM::new(program.trim_end_matches('\n'));
+    for &(addr, value) in &options.pokes {
+        vm.mem[addr] = value;
+    }
+    let script = match &options.script {
+        Some(path) => fs::read_to_string(path)
+            .with_context(|| format!("failed to read {path}"))?
+            .lines()
+            .map(str::to_string)
+            .collect(),
+        None => VecDeque::new(),
+    };
+    let transcript = match &options.transcript {
+        Some(path) => Some(File::create(path).with_context(|| format!("failed to create {path}"))?),
+        None => None,
+    };
+    let screen = if options.tiles {
+        Some(Screen {
+            terminal: Terminal::new(io::stdout())?,
+            pending: Vec::new(),
+            height: 0,
+        })
+    } else {
+        None
+    };
+
+    let mut session = Session {
+        vm,
+        script,
+        keyboard: (!io::stdin().is_terminal()).then(|| io::stdin().lines()),
+        history: Vec::new(),
+        transcript,
+        screen,
+        queued: VecDeque::new(),
+        ascii: false,
+        line_start: true,
+    };
+    session.run()
+}
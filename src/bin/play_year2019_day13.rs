@@ -1,28 +1,29 @@
-use std::{thread::sleep, time::Duration};
+use std::{io, thread::sleep, time::Duration};
 
-use advent_of_code::{get_input, solutions::year2019::day13::play};
+use advent_of_code::{
+    get_input,
+    grid::Vector,
+    render::Terminal,
+    solutions::year2019::day13::{play, tile},
+};
+use anyhow::Result;
 
-fn main() {
-    println!("\u{009B}?25l\u{009B}2J");
+fn main() -> Result<()> {
+    let mut terminal = Terminal::new(io::stdout())?;
+    let mut res = Ok(());
     play(get_input(2019, 13).trim_end_matches('\n'), |x, y, z| {
-        if x == -1 && y == 0 {
-            println!("\u{009B}1;2H{z}\u{0007}");
-        } else {
-            println!(
-                "\u{009B}{};{}H{}",
-                y + 2,
-                x + 2,
-                match z {
-                    0 => ' ',
-                    1 => '#',
-                    2 => '=',
-                    3 => '-',
-                    4 => 'o',
-                    _ => unreachable!(),
-                }
-            );
+        if res.is_err() {
+            return;
         }
+        let x = i32::try_from(x).unwrap();
+        let y = i32::try_from(y).unwrap();
+        res = if x == -1 && y == 0 {
+            terminal.draw_str(Vector::new(1, 0), &format!("{z}\u{0007}"))
+        } else {
+            terminal.draw_cell(Vector::new(x + 1, y + 1), tile(z).unwrap())
+        };
         sleep(Duration::from_millis(5));
     });
-    println!("\u{009B}?25h\u{009B}28;0H");
+    res?;
+    Ok(())
 }
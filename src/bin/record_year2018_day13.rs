@@ -0,0 +1,11 @@
+use std::fs::File;
+
+use advent_of_code::{get_input, solutions::year2018::day13::record};
+use anyhow::Result;
+
+fn main() -> Result<()> {
+    let recorder = record(&get_input(2018, 13));
+    println!("{} frames", recorder.len());
+    recorder.write_asciicast(File::create("record_year2018_day13.cast")?)?;
+    Ok(())
+}
@@ -0,0 +1,11 @@
+use std::fs::File;
+
+use advent_of_code::{get_input, solutions::year2024::day15::record};
+use anyhow::Result;
+
+fn main() -> Result<()> {
+    let recorder = record(&get_input(2024, 15));
+    println!("{} frames", recorder.len());
+    recorder.write_asciicast(File::create("record_year2024_day15.cast")?)?;
+    Ok(())
+}
@@ -0,0 +1,11 @@
+use std::fs::File;
+
+use advent_of_code::{get_input, solutions::year2021::day15::trace};
+use anyhow::Result;
+
+fn main() -> Result<()> {
+    let (stats, frame) = trace(&get_input(2021, 15));
+    println!("{stats}");
+    frame.write_png(File::create("trace_year2021_day15.png")?, 2)?;
+    Ok(())
+}
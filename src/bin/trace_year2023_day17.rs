@@ -0,0 +1,12 @@
+use std::fs::File;
+
+use advent_of_code::{get_input, solutions::year2023::day17::trace};
+use anyhow::Result;
+
+fn main() -> Result<()> {
+    for (name, heat_loss, stats, frame) in trace(&get_input(2023, 17)) {
+        println!("{name}: heat loss {heat_loss}, {stats}");
+        frame.write_png(File::create(format!("trace_year2023_day17_{name}.png"))?, 4)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,161 @@
+use std::{collections::HashMap, hash::Hash};
+
+/// A sequence of states which repeats with period `len` from index `start` onwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub len: usize,
+}
+
+impl Cycle {
+    /// The smallest index which holds the same state as index `n`.
+    pub fn reduce(self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.len
+        }
+    }
+
+    /// The state at index `n` of the sequence beginning at `start`. Never steps more than
+    /// `self.start + self.len` times, however large `n` is.
+    pub fn state_at<S, F>(self, start: S, mut step: F, n: usize) -> S
+    where
+        F: FnMut(&S) -> S,
+    {
+        (0..self.reduce(n)).fold(start, |state, _| step(&state))
+    }
+}
+
+/// Finds the cycle in the sequence `start, step(start), step(step(start)), ...` by remembering the
+/// hash key of every state. Steps the minimum number of times, but the memory used grows with
+/// `start + len`.
+pub fn hashed_by_key<S, F, H, K>(mut state: S, mut step: F, mut hash_key: H) -> Cycle
+where
+    F: FnMut(&S) -> S,
+    H: FnMut(&S) -> K,
+    K: Eq + Hash,
+{
+    let mut seen = HashMap::new();
+    for i in 0.. {
+        if let Some(start) = seen.insert(hash_key(&state), i) {
+            return Cycle {
+                start,
+                len: i - start,
+            };
+        }
+        state = step(&state);
+    }
+    unreachable!()
+}
+
+/// Like `hashed_by_key` but compares whole states.
+pub fn hashed<S, F>(start: S, step: F) -> Cycle
+where
+    S: Clone + Eq + Hash,
+    F: FnMut(&S) -> S,
+{
+    hashed_by_key(start, step, Clone::clone)
+}
+
+/// Finds the cycle using
+/// [Floyd's tortoise and hare](https://en.wikipedia.org/wiki/Cycle_detection#Floyd's_tortoise_and_hare).
+/// Only ever holds two states at once.
+pub fn floyd_by_key<S, F, H, K>(start: S, mut step: F, mut key: H) -> Cycle
+where
+    F: FnMut(&S) -> S,
+    H: FnMut(&S) -> K,
+    K: Eq,
+{
+    // Find some i > 0 where state i equals state 2i. i is a multiple of len.
+    let mut tortoise = step(&start);
+    let mut hare = step(&tortoise);
+    while key(&tortoise) != key(&hare) {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        hare = step(&hare);
+    }
+
+    // Walking in lockstep from 0 and from i, the first meeting point is the start of the cycle.
+    let mut cycle_start = 0;
+    tortoise = start;
+    while key(&tortoise) != key(&hare) {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        cycle_start += 1;
+    }
+
+    let mut len = 1;
+    hare = step(&tortoise);
+    while key(&tortoise) != key(&hare) {
+        hare = step(&hare);
+        len += 1;
+    }
+
+    Cycle {
+        start: cycle_start,
+        len,
+    }
+}
+
+/// Like `floyd_by_key` but compares whole states.
+pub fn floyd<S, F>(start: S, step: F) -> Cycle
+where
+    S: Clone + Eq,
+    F: FnMut(&S) -> S,
+{
+    floyd_by_key(start, step, Clone::clone)
+}
+
+/// Finds the cycle using
+/// [Brent's algorithm](https://en.wikipedia.org/wiki/Cycle_detection#Brent's_algorithm). Holds at
+/// most two states and one key at once, and typically steps fewer times than `floyd_by_key`, so
+/// this is the one to reach for when states are large (whole `Grid`s, say) and a `seen` map would
+/// use too much memory.
+pub fn brent_by_key<S, F, H, K>(start: S, mut step: F, mut key: H) -> Cycle
+where
+    F: FnMut(&S) -> S,
+    H: FnMut(&S) -> K,
+    K: Eq,
+{
+    // Find len by moving the tortoise up to the hare at increasing powers of two, and then counting
+    // how many steps it takes the hare to catch up again.
+    let mut power = 1;
+    let mut len = 1;
+    let mut tortoise = key(&start);
+    let mut hare = step(&start);
+    while tortoise != key(&hare) {
+        if power == len {
+            tortoise = key(&hare);
+            power *= 2;
+            len = 0;
+        }
+        hare = step(&hare);
+        len += 1;
+    }
+
+    // With the hare len steps ahead of the tortoise, the first meeting point is the start of the
+    // cycle.
+    let mut hare = (1..len).fold(step(&start), |state, _| step(&state));
+    let mut tortoise = start;
+    let mut cycle_start = 0;
+    while key(&tortoise) != key(&hare) {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        cycle_start += 1;
+    }
+
+    Cycle {
+        start: cycle_start,
+        len,
+    }
+}
+
+/// Like `brent_by_key` but compares whole states.
+pub fn brent<S, F>(start: S, step: F) -> Cycle
+where
+    S: Clone + Eq,
+    F: FnMut(&S) -> S,
+{
+    brent_by_key(start, step, Clone::clone)
+}
@@ -0,0 +1,171 @@
+use std::{
+    collections::{HashMap, HashSet},
+    hash::BuildHasher,
+};
+
+use num::Integer;
+
+use crate::{
+    grid::{Adjacent, E, Grid, N, S, Vector, W},
+    search,
+};
+
+/// Twice the signed area of the polygon with the given vertices (doubled so that it's always an
+/// integer). Positive if the vertices go anticlockwise when y points up (or clockwise on screen,
+/// with y pointing down). See the
+/// [shoelace formula](https://en.wikipedia.org/wiki/Shoelace_formula).
+pub fn double_area(vertices: &[Vector]) -> i64 {
+    edges(vertices)
+        .map(|(a, b)| i64::from(a.x) * i64::from(b.y) - i64::from(b.x) * i64::from(a.y))
+        .sum()
+}
+
+/// The number of lattice points on the boundary of the polygon with the given vertices.
+pub fn boundary_points(vertices: &[Vector]) -> i64 {
+    edges(vertices)
+        .map(|(a, b)| i64::from((b.x - a.x).abs().gcd(&(b.y - a.y).abs())))
+        .sum()
+}
+
+/// The number of lattice points strictly inside the polygon with the given vertices, by
+/// [Pick's theorem](https://en.wikipedia.org/wiki/Pick%27s_theorem).
+pub fn interior_points(vertices: &[Vector]) -> i64 {
+    (double_area(vertices).abs() - boundary_points(vertices)) / 2 + 1
+}
+
+/// True if `point` is inside or on the boundary of the polygon with the given vertices.
+pub fn polygon_contains(vertices: &[Vector], point: Vector) -> bool {
+    let mut inside = false;
+    for (a, b) in edges(vertices) {
+        let (ab, ap) = (b - a, point - a);
+        let cross = i64::from(ab.x) * i64::from(ap.y) - i64::from(ab.y) * i64::from(ap.x);
+        if cross == 0
+            && a.x.min(b.x) <= point.x
+            && point.x <= a.x.max(b.x)
+            && a.y.min(b.y) <= point.y
+            && point.y <= a.y.max(b.y)
+        {
+            return true;
+        }
+        // Cast a ray in the E direction and count crossings. Treating each edge as half open in y
+        // means a ray through a vertex is counted once.
+        if (a.y > point.y) != (b.y > point.y) {
+            let crosses_east = if b.y > a.y { cross > 0 } else { cross < 0 };
+            if crosses_east {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+fn edges(vertices: &[Vector]) -> impl Iterator<Item = (Vector, Vector)> + '_ {
+    vertices
+        .iter()
+        .zip(vertices.iter().cycle().skip(1))
+        .map(|(&a, &b)| (a, b))
+}
+
+/// Something which assigns values to lattice points, so that we can talk about connected regions
+/// of equal value. Implemented for dense `Grid`s and for sparse maps and sets.
+pub trait Lattice {
+    type Value: Eq;
+
+    fn value(&self, v: Vector) -> Option<&Self::Value>;
+    fn positions(&self) -> impl Iterator<Item = Vector>;
+}
+
+impl<T: Eq> Lattice for Grid<T> {
+    type Value = T;
+
+    fn value(&self, v: Vector) -> Option<&T> {
+        self.get(v)
+    }
+
+    fn positions(&self) -> impl Iterator<Item = Vector> {
+        self.keys()
+    }
+}
+
+impl<T: Eq, S: BuildHasher> Lattice for HashMap<Vector, T, S> {
+    type Value = T;
+
+    fn value(&self, v: Vector) -> Option<&T> {
+        self.get(&v)
+    }
+
+    fn positions(&self) -> impl Iterator<Item = Vector> {
+        self.keys().copied()
+    }
+}
+
+impl<S: BuildHasher> Lattice for HashSet<Vector, S> {
+    type Value = ();
+
+    fn value(&self, v: Vector) -> Option<&()> {
+        self.contains(&v).then_some(&())
+    }
+
+    fn positions(&self) -> impl Iterator<Item = Vector> {
+        self.iter().copied()
+    }
+}
+
+/// The orthogonally connected region of positions with the same value as `start`, which is empty
+/// if `start` isn't in the lattice.
+pub fn region<L: Lattice>(lattice: &L, start: Vector) -> HashSet<Vector> {
+    let Some(value) = lattice.value(start) else {
+        return HashSet::new();
+    };
+    search::breadth_first(
+        start,
+        |&v, push| {
+            v.adjacent4()
+                .filter(|&u| lattice.value(u) == Some(value))
+                .for_each(push);
+        },
+        search::id_filter(),
+    )
+    .collect()
+}
+
+/// Break a lattice up in to orthogonally connected regions of equal value.
+pub fn regions<L: Lattice>(lattice: &L) -> Vec<HashSet<Vector>> {
+    let mut seen: HashSet<Vector> = HashSet::new();
+    let mut res = Vec::new();
+    for v in lattice.positions() {
+        if !seen.contains(&v) {
+            let region = region(lattice, v);
+            seen.extend(&region);
+            res.push(region);
+        }
+    }
+    res
+}
+
+/// The number of unit edges separating `region` from the positions outside it.
+pub fn perimeter<S: BuildHasher>(region: &HashSet<Vector, S>) -> usize {
+    region
+        .iter()
+        .flat_map(|&v| v.adjacent4())
+        .filter(|u| !region.contains(u))
+        .count()
+}
+
+/// The number of corners of `region`, counting the corners of any holes. This is also the number
+/// of straight sides.
+pub fn corners<S: BuildHasher>(region: &HashSet<Vector, S>) -> usize {
+    region
+        .iter()
+        .flat_map(|&v| {
+            [(N, E), (E, S), (S, W), (W, N)]
+                .into_iter()
+                .filter(move |&(a, b)| {
+                    let a_in = region.contains(&(v + a));
+                    let b_in = region.contains(&(v + b));
+                    // Either a convex corner, or a concave one with the diagonal outside.
+                    !a_in && !b_in || a_in && b_in && !region.contains(&(v + a + b))
+                })
+        })
+        .count()
+}
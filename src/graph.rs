@@ -0,0 +1,663 @@
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet},
+    hash::Hash,
+    mem,
+    ops::{Index, Range},
+};
+
+use num::Zero;
+
+use crate::{
+    grid::{Adjacent, E, Grid, S, Vector},
+    search,
+    uniq::Uniq,
+};
+
+/// A graph with interned nodes: each distinct `N` added is given a dense `usize` id, and everything
+/// else (neighbours, components, cliques, ...) is in terms of those ids. Index the graph with an id
+/// to get the node back. Edges carry a weight `W`, which is `()` for unweighted graphs.
+#[derive(Clone)]
+pub struct Graph<N, W> {
+    directed: bool,
+    ids: HashMap<N, usize>,
+    nodes: Vec<N>,
+    out: Vec<BTreeMap<usize, W>>,
+    // Incoming edges, only maintained for directed graphs.
+    into: Vec<BTreeMap<usize, W>>,
+}
+
+impl<N, W> Graph<N, W>
+where
+    N: Clone + Eq + Hash,
+    W: Clone,
+{
+    pub fn directed() -> Self {
+        Self::new(true)
+    }
+
+    pub fn undirected() -> Self {
+        Self::new(false)
+    }
+
+    fn new(directed: bool) -> Self {
+        Self {
+            directed,
+            ids: HashMap::new(),
+            nodes: Vec::new(),
+            out: Vec::new(),
+            into: Vec::new(),
+        }
+    }
+
+    pub fn is_directed(&self) -> bool {
+        self.directed
+    }
+
+    /// The id of `node`, adding it to the graph if it's new.
+    pub fn add_node(&mut self, node: N) -> usize {
+        if let Some(&id) = self.ids.get(&node) {
+            return id;
+        }
+        let id = self.nodes.len();
+        self.ids.insert(node.clone(), id);
+        self.nodes.push(node);
+        self.out.push(BTreeMap::new());
+        if self.directed {
+            self.into.push(BTreeMap::new());
+        }
+        id
+    }
+
+    /// Add an edge from `a` to `b` (and so from `b` to `a` if the graph is undirected), adding the
+    /// nodes if they're new and replacing any existing edge.
+    pub fn add_edge(&mut self, a: N, b: N, weight: W) {
+        let a = self.add_node(a);
+        let b = self.add_node(b);
+        self.out[a].insert(b, weight.clone());
+        if self.directed {
+            self.into[b].insert(a, weight);
+        } else {
+            self.out[b].insert(a, weight);
+        }
+    }
+
+    /// Remove the edge from `a` to `b` (and from `b` to `a` if the graph is undirected), returning
+    /// its weight.
+    pub fn remove_edge(&mut self, a: usize, b: usize) -> Option<W> {
+        let res = self.out[a].remove(&b);
+        if self.directed {
+            self.into[b].remove(&a);
+        } else {
+            self.out[b].remove(&a);
+        }
+        res
+    }
+
+    pub fn id(&self, node: &N) -> Option<usize> {
+        self.ids.get(node).copied()
+    }
+
+    pub fn ids(&self) -> Range<usize> {
+        0..self.nodes.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn edge(&self, a: usize, b: usize) -> Option<&W> {
+        self.out[a].get(&b)
+    }
+
+    /// The nodes reachable from `id` along one edge, with the weights of those edges.
+    pub fn neighbors(&self, id: usize) -> impl Iterator<Item = (usize, &W)> {
+        self.out[id].iter().map(|(&b, w)| (b, w))
+    }
+
+    /// The nodes from which `id` is reachable along one edge, with the weights of those edges. The
+    /// same as `neighbors` for undirected graphs.
+    pub fn predecessors(&self, id: usize) -> impl Iterator<Item = (usize, &W)> {
+        let edges = if self.directed {
+            &self.into[id]
+        } else {
+            &self.out[id]
+        };
+        edges.iter().map(|(&a, w)| (a, w))
+    }
+
+    pub fn degree(&self, id: usize) -> usize {
+        self.out[id].len()
+    }
+
+    /// Every edge, as (from, to, weight). Undirected edges are only included once, from the lower
+    /// id to the higher.
+    pub fn edges(&self) -> impl Iterator<Item = (usize, usize, &W)> {
+        self.ids().flat_map(move |a| {
+            self.neighbors(a)
+                .filter(move |&(b, _)| self.directed || a <= b)
+                .map(move |(b, w)| (a, b, w))
+        })
+    }
+
+    /// The connected components of the graph, ignoring the direction of edges.
+    pub fn components(&self) -> Vec<Vec<usize>> {
+        let mut seen = vec![false; self.len()];
+        let mut res = Vec::new();
+        for start in self.ids() {
+            if seen[start] {
+                continue;
+            }
+            seen[start] = true;
+            let mut component = Vec::new();
+            let mut stack = vec![start];
+            while let Some(a) = stack.pop() {
+                component.push(a);
+                for (b, _) in self.neighbors(a).chain(self.predecessors(a)) {
+                    if !seen[b] {
+                        seen[b] = true;
+                        stack.push(b);
+                    }
+                }
+            }
+            component.sort_unstable();
+            res.push(component);
+        }
+        res
+    }
+
+    /// The nodes in an order where every edge goes forwards, or `None` if there's a cycle. Ties are
+    /// broken by taking the smallest available node first, so the order is unique.
+    pub fn toposort(&self) -> Option<Vec<usize>>
+    where
+        N: Ord,
+    {
+        let mut in_degree: Vec<usize> =
+            self.ids().map(|id| self.predecessors(id).count()).collect();
+        let mut available: BinaryHeap<_> = self
+            .ids()
+            .filter(|&id| in_degree[id] == 0)
+            .map(|id| Reverse((&self.nodes[id], id)))
+            .collect();
+        let mut res = Vec::new();
+        while let Some(Reverse((_, a))) = available.pop() {
+            res.push(a);
+            for (b, _) in self.neighbors(a) {
+                in_degree[b] -= 1;
+                if in_degree[b] == 0 {
+                    available.push(Reverse((&self.nodes[b], b)));
+                }
+            }
+        }
+        (res.len() == self.len()).then_some(res)
+    }
+
+    /// The strongly connected components of the graph, by
+    /// [Tarjan's algorithm](https://en.wikipedia.org/wiki/Tarjan%27s_strongly_connected_components_algorithm).
+    /// Components come out in reverse topological order: no edge leads from a component to a later
+    /// one.
+    pub fn sccs(&self) -> Vec<Vec<usize>> {
+        struct Tarjan<'a, N, W> {
+            graph: &'a Graph<N, W>,
+            next_index: usize,
+            index: Vec<Option<usize>>,
+            low_link: Vec<usize>,
+            stack: Vec<usize>,
+            on_stack: Vec<bool>,
+            res: Vec<Vec<usize>>,
+        }
+
+        impl<N, W> Tarjan<'_, N, W>
+        where
+            N: Clone + Eq + Hash,
+            W: Clone,
+        {
+            fn visit(&mut self, a: usize) {
+                self.index[a] = Some(self.next_index);
+                self.low_link[a] = self.next_index;
+                self.next_index += 1;
+                self.stack.push(a);
+                self.on_stack[a] = true;
+
+                for (b, _) in self.graph.neighbors(a) {
+                    if let Some(index) = self.index[b] {
+                        if self.on_stack[b] {
+                            self.low_link[a] = self.low_link[a].min(index);
+                        }
+                    } else {
+                        self.visit(b);
+                        self.low_link[a] = self.low_link[a].min(self.low_link[b]);
+                    }
+                }
+
+                if Some(self.low_link[a]) == self.index[a] {
+                    let mut component = Vec::new();
+                    loop {
+                        let b = self.stack.pop().unwrap();
+                        self.on_stack[b] = false;
+                        component.push(b);
+                        if b == a {
+                            break;
+                        }
+                    }
+                    component.sort_unstable();
+                    self.res.push(component);
+                }
+            }
+        }
+
+        let mut tarjan = Tarjan {
+            graph: self,
+            next_index: 0,
+            index: vec![None; self.len()],
+            low_link: vec![0; self.len()],
+            stack: Vec::new(),
+            on_stack: vec![false; self.len()],
+            res: Vec::new(),
+        };
+        for a in self.ids() {
+            if tarjan.index[a].is_none() {
+                tarjan.visit(a);
+            }
+        }
+        tarjan.res
+    }
+
+    /// A largest set of nodes which are all connected to each other, by the
+    /// [Bron–Kerbosch algorithm](https://en.wikipedia.org/wiki/Bron%E2%80%93Kerbosch_algorithm)
+    /// with pivoting. Edges are treated as undirected.
+    pub fn max_clique(&self) -> Vec<usize> {
+        fn bron_kerbosch(
+            neighbors: &[HashSet<usize>],
+            r: &mut Vec<usize>,
+            mut p: HashSet<usize>,
+            mut x: HashSet<usize>,
+            best: &mut Vec<usize>,
+        ) {
+            if p.is_empty() && x.is_empty() {
+                if r.len() > best.len() {
+                    best.clone_from(r);
+                }
+                return;
+            }
+            if r.len() + p.len() <= best.len() {
+                return;
+            }
+            // Any maximal clique contains the pivot or one of its non-neighbours, so we only need
+            // to branch on those.
+            let pivot = *p
+                .union(&x)
+                .max_by_key(|&&u| p.intersection(&neighbors[u]).count())
+                .unwrap();
+            let candidates: Vec<usize> = p.difference(&neighbors[pivot]).copied().collect();
+            for v in candidates {
+                r.push(v);
+                bron_kerbosch(
+                    neighbors,
+                    r,
+                    p.intersection(&neighbors[v]).copied().collect(),
+                    x.intersection(&neighbors[v]).copied().collect(),
+                    best,
+                );
+                r.pop();
+                p.remove(&v);
+                x.insert(v);
+            }
+        }
+
+        let neighbors: Vec<HashSet<usize>> = self
+            .ids()
+            .map(|a| {
+                self.neighbors(a)
+                    .chain(self.predecessors(a))
+                    .map(|(b, _)| b)
+                    .filter(|&b| b != a)
+                    .collect()
+            })
+            .collect();
+        let mut best = Vec::new();
+        bron_kerbosch(
+            &neighbors,
+            &mut Vec::new(),
+            self.ids().collect(),
+            HashSet::new(),
+            &mut best,
+        );
+        best.sort_unstable();
+        best
+    }
+
+    /// The minimum total weight of edges which must be removed to split the graph in two, along
+    /// with the nodes on one side of such a cut, by the
+    /// [Stoer–Wagner algorithm](https://en.wikipedia.org/wiki/Stoer%E2%80%93Wagner_algorithm).
+    /// Edges are treated as undirected. `None` if there are fewer than two nodes.
+    pub fn min_cut(&self) -> Option<(W, Vec<usize>)>
+    where
+        W: Copy + Ord + Zero,
+    {
+        let mut adjacent: Vec<HashMap<usize, W>> = vec![HashMap::new(); self.len()];
+        for (a, b, &w) in self.edges() {
+            if a != b {
+                for (a, b) in [(a, b), (b, a)] {
+                    let weight = adjacent[a].entry(b).or_insert_with(W::zero);
+                    *weight = *weight + w;
+                }
+            }
+        }
+        // Each remaining node stands for the set of original nodes merged in to it.
+        let mut members: Vec<Vec<usize>> = self.ids().map(|a| vec![a]).collect();
+        let mut remaining: Vec<usize> = self.ids().collect();
+        let mut best: Option<(W, Vec<usize>)> = None;
+
+        while remaining.len() > 1 {
+            // Add nodes one at a time, always picking the one most tightly connected to those
+            // added so far. The weight connecting the last node to the rest is a cut, and the
+            // last two nodes can be merged without losing any better cuts.
+            let mut weights: HashMap<usize, W> = HashMap::from([(remaining[0], W::zero())]);
+            let mut heap = BinaryHeap::from([(W::zero(), remaining[0])]);
+            let mut added = HashSet::new();
+            let (mut s, mut t, mut cut) = (remaining[0], remaining[0], W::zero());
+            while let Some((w, a)) = heap.pop() {
+                if added.contains(&a) || weights[&a] != w {
+                    continue;
+                }
+                added.insert(a);
+                (s, t, cut) = (t, a, w);
+                for (&b, &c) in &adjacent[a] {
+                    if !added.contains(&b) {
+                        let weight = weights.entry(b).or_insert_with(W::zero);
+                        *weight = *weight + c;
+                        heap.push((*weight, b));
+                    }
+                }
+            }
+
+            if added.len() < remaining.len() {
+                // The graph is disconnected, so there's a cut of zero.
+                let side = added.iter().flat_map(|&a| &members[a]).copied().collect();
+                return Some((W::zero(), side));
+            }
+
+            if best.as_ref().is_none_or(|&(w, _)| cut < w) {
+                best = Some((cut, members[t].clone()));
+            }
+
+            let t_members = mem::take(&mut members[t]);
+            members[s].extend(t_members);
+            for (b, c) in mem::take(&mut adjacent[t]) {
+                adjacent[b].remove(&t);
+                if b != s {
+                    for (a, b) in [(s, b), (b, s)] {
+                        let weight = adjacent[a].entry(b).or_insert_with(W::zero);
+                        *weight = *weight + c;
+                    }
+                }
+            }
+            remaining.retain(|&a| a != t);
+        }
+
+        best.map(|(w, mut side)| {
+            side.sort_unstable();
+            (w, side)
+        })
+    }
+
+    /// The total weight of a shortest route visiting every node exactly once, starting from
+    /// `start` if given (or anywhere otherwise), and returning to the start at the end if
+    /// `round_trip`. `None` if there's no such route. See `held_karp`.
+    pub fn shortest_route(&self, start: Option<usize>, round_trip: bool) -> Option<W>
+    where
+        W: Copy + Ord + Zero,
+    {
+        self.held_karp(start, round_trip, Ord::min)
+    }
+
+    /// Like `shortest_route` but longest.
+    pub fn longest_route(&self, start: Option<usize>, round_trip: bool) -> Option<W>
+    where
+        W: Copy + Ord + Zero,
+    {
+        self.held_karp(start, round_trip, Ord::max)
+    }
+
+    /// The [Held–Karp algorithm](https://en.wikipedia.org/wiki/Held%E2%80%93Karp_algorithm), a
+    /// dynamic programme over the best weight of a route through each subset of nodes (as a
+    /// bitmask) ending at each node. Takes time proportional to 2^n n^2 for n nodes, as opposed to
+    /// the n! of trying every permutation.
+    fn held_karp<F>(&self, start: Option<usize>, round_trip: bool, mut better: F) -> Option<W>
+    where
+        W: Copy + Zero,
+        F: FnMut(W, W) -> W,
+    {
+        let n = self.len();
+        assert!(n < 32, "too many nodes for Held–Karp: {n}");
+        // A round trip visits every node whichever one it starts from.
+        let start = start.or(round_trip.then_some(0));
+        let mut best: Vec<Vec<Option<W>>> = vec![vec![None; n]; 1 << n];
+        for a in self.ids() {
+            if start.is_none_or(|start| start == a) {
+                best[1 << a][a] = Some(W::zero());
+            }
+        }
+        for visited in 1..1 << n {
+            for a in self.ids() {
+                let Some(w) = best[visited][a] else {
+                    continue;
+                };
+                for (b, &c) in self.neighbors(a) {
+                    if visited & 1 << b == 0 {
+                        let entry = &mut best[visited | 1 << b][b];
+                        *entry = Some(entry.map_or(w + c, |e| better(e, w + c)));
+                    }
+                }
+            }
+        }
+        self.ids()
+            .filter_map(|a| {
+                let w = best[(1 << n) - 1][a]?;
+                if round_trip {
+                    Some(w + *self.edge(a, start?)?)
+                } else {
+                    Some(w)
+                }
+            })
+            .reduce(better)
+    }
+
+    /// The total weight of a longest path from `start` to `end` which doesn't visit any node
+    /// twice, by depth first search over every simple path with the visited nodes kept in a
+    /// bitmask. Only practical for small graphs (such as a maze compressed with `from_maze`).
+    /// `None` if `end` can't be reached.
+    ///
+    /// Memoising on the current node and the visited set turns out to cost far more than it saves,
+    /// since the same pair almost never comes up twice.
+    pub fn longest_path(&self, start: usize, end: usize) -> Option<W>
+    where
+        W: Copy + Ord + Zero,
+    {
+        fn longest<N, W>(
+            graph: &Graph<N, W>,
+            a: usize,
+            end: usize,
+            last: Option<usize>,
+            visited: u64,
+        ) -> Option<W>
+        where
+            N: Clone + Eq + Hash,
+            W: Copy + Ord + Zero,
+        {
+            if a == end {
+                return Some(W::zero());
+            }
+            let mut res = None;
+            for (b, &w) in graph.neighbors(a) {
+                // If there's only one way in to the end then we have to take it as soon as we get
+                // the chance, or we'll never get there.
+                if visited & 1 << b != 0 || last == Some(a) && b != end {
+                    continue;
+                }
+                if let Some(rest) = longest(graph, b, end, last, visited | 1 << b) {
+                    res = res.max(Some(w + rest));
+                }
+            }
+            res
+        }
+
+        assert!(
+            self.len() <= 64,
+            "too many nodes for longest_path: {}",
+            self.len()
+        );
+        let mut ins = self.predecessors(end).map(|(a, _)| a);
+        let last = ins.next().filter(|_| ins.next().is_none());
+        longest(self, start, end, last, 1 << start)
+    }
+}
+
+impl Graph<Vector, u32> {
+    /// The undirected graph of orthogonally adjacent positions in `grid` for which `open` is true,
+    /// with every edge of weight 1.
+    pub fn from_grid<T, F>(grid: &Grid<T>, mut open: F) -> Self
+    where
+        F: FnMut(Vector, &T) -> bool,
+    {
+        let open = grid.map(|v, value| open(v, value));
+        let mut res = Self::undirected();
+        for v in open.keys().filter(|&v| open[v]) {
+            res.add_node(v);
+            for u in [v + E, v + S] {
+                if open.get(u) == Some(&true) {
+                    res.add_edge(v, u, 1);
+                }
+            }
+        }
+        res
+    }
+}
+
+/// A corridor through a maze between two interesting cells: the number of steps along it and the
+/// doors (or whatever else needs unlocking) it passes through.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Corridor<D> {
+    pub steps: u32,
+    pub doors: BTreeSet<D>,
+}
+
+impl Graph<Vector, u32> {
+    /// Compress a maze down to its interesting cells (junctions, keys, the start and end, ...). See
+    /// `from_maze_with_doors`, which this is without the doors.
+    pub fn from_maze<T, P, I>(grid: &Grid<T>, passable: P, interesting: I) -> Self
+    where
+        P: FnMut(Vector, Vector) -> bool,
+        I: IntoIterator<Item = Vector>,
+    {
+        let mut res = Self::directed();
+        for (a, b, corridor) in corridors(grid, passable, interesting, |_| None::<()>) {
+            res.add_node(a);
+            if let Some(b) = b {
+                res.add_edge(a, b, corridor.steps);
+            }
+        }
+        res
+    }
+}
+
+impl<D: Clone + Ord> Graph<Vector, Corridor<D>> {
+    /// Compress a maze down to a directed graph of its interesting cells, with an edge from `a` to
+    /// `b` for the shortest walk from `a` to `b` which doesn't pass through any other interesting
+    /// cell. `passable(from, to)` says whether we can step between two orthogonally adjacent
+    /// positions in the grid, so one way passages are fine. `door` returns what's needed to pass
+    /// through a cell, if anything, and each edge records everything needed along the way.
+    pub fn from_maze_with_doors<T, P, I, F>(
+        grid: &Grid<T>,
+        passable: P,
+        interesting: I,
+        door: F,
+    ) -> Self
+    where
+        P: FnMut(Vector, Vector) -> bool,
+        I: IntoIterator<Item = Vector>,
+        F: FnMut(Vector) -> Option<D>,
+    {
+        let mut res = Self::directed();
+        for (a, b, corridor) in corridors(grid, passable, interesting, door) {
+            res.add_node(a);
+            if let Some(b) = b {
+                res.add_edge(a, b, corridor);
+            }
+        }
+        res
+    }
+}
+
+// Every corridor leading out of each interesting cell. Each interesting cell also comes with a
+// `None` destination, so that cells with no way out still make it in to the graph.
+fn corridors<T, P, I, F, D>(
+    grid: &Grid<T>,
+    mut passable: P,
+    interesting: I,
+    mut door: F,
+) -> Vec<(Vector, Option<Vector>, Corridor<D>)>
+where
+    P: FnMut(Vector, Vector) -> bool,
+    I: IntoIterator<Item = Vector>,
+    F: FnMut(Vector) -> Option<D>,
+    D: Clone + Ord,
+{
+    #[derive(Clone)]
+    struct State<D> {
+        pos: Vector,
+        corridor: Corridor<D>,
+    }
+
+    // Keep the order we were given, so that node ids are predictable.
+    let order: Vec<Vector> = interesting.into_iter().uniq().collect();
+    let interesting: HashSet<Vector> = order.iter().copied().collect();
+    let mut res = Vec::new();
+    for start in order {
+        let none = Corridor {
+            steps: 0,
+            doors: BTreeSet::new(),
+        };
+        res.push((start, None, none.clone()));
+        let ends: Vec<State<D>> = search::breadth_first(
+            State {
+                pos: start,
+                corridor: none,
+            },
+            |state, push| {
+                if state.pos != start && interesting.contains(&state.pos) {
+                    return;
+                }
+                for pos in state.pos.adjacent4() {
+                    if grid.contains_key(pos) && passable(state.pos, pos) {
+                        let mut corridor = state.corridor.clone();
+                        corridor.steps += 1;
+                        corridor.doors.extend(door(pos));
+                        push(State { pos, corridor });
+                    }
+                }
+            },
+            search::hash_filter(|state: &State<D>| state.pos),
+        )
+        .filter(|state| state.pos != start && interesting.contains(&state.pos))
+        .collect();
+        res.extend(
+            ends.into_iter()
+                .map(|state| (start, Some(state.pos), state.corridor)),
+        );
+    }
+    res
+}
+
+impl<N, W> Index<usize> for Graph<N, W> {
+    type Output = N;
+
+    fn index(&self, id: usize) -> &N {
+        &self.nodes[id]
+    }
+}
@@ -1,4 +1,6 @@
 use std::{
+    array,
+    collections::HashMap,
     fmt::{self, Write},
     iter,
     ops::{Index, IndexMut},
@@ -164,6 +166,14 @@ pub fn scan(s: &str) -> impl Iterator<Item = (Vector, char)> + '_ {
     })
 }
 
+/// Parse `s` into a map containing only the positions for which `f` returns `Some`.
+pub fn parse_sparse<T, F: FnMut(Vector, char) -> Option<T>>(
+    s: &str,
+    mut f: F,
+) -> HashMap<Vector, T> {
+    scan(s).filter_map(|(v, c)| Some((v, f(v, c)?))).collect()
+}
+
 #[derive(Clone, PartialEq, Eq, Hash)]
 pub struct Grid<T> {
     data: Vec<T>,
@@ -205,6 +215,46 @@ impl<T> Grid<T> {
         Self::from_vec(size, data)
     }
 
+    /// Like `parse`, but rows shorter than the longest are padded out with `fill` rather than
+    /// rejected.
+    pub fn parse_padded<F: FnMut(Vector, char) -> T>(s: &str, fill: T, mut f: F) -> Self
+    where
+        T: Clone,
+    {
+        let size = scan(s).fold(Z, |size, (v, _)| size.sup(&(v + SE)));
+        let mut res = Self::new(fill, size);
+        for (v, c) in scan(s) {
+            res[v] = f(v, c);
+        }
+        res
+    }
+
+    /// Parse each blank line separated block of `s` as a separate grid.
+    pub fn parse_blocks<F: FnMut(Vector, char) -> T>(s: &str, mut f: F) -> Vec<Self> {
+        s.split("\n\n")
+            .map(|block| Self::parse(block, &mut f))
+            .collect()
+    }
+
+    /// Like `parse`, but also returns the position of each of the `markers` (a start and end
+    /// position, say), each of which must appear exactly once.
+    pub fn parse_find<F: FnMut(Vector, char) -> T, const M: usize>(
+        s: &str,
+        markers: [char; M],
+        mut f: F,
+    ) -> (Self, [Vector; M]) {
+        let mut found = [None; M];
+        let grid = Self::parse(s, |v, c| {
+            if let Some(i) = markers.iter().position(|&marker| marker == c) {
+                assert!(found[i].is_none(), "found more than one {c}");
+                found[i] = Some(v);
+            }
+            f(v, c)
+        });
+        let found = array::from_fn(|i| found[i].unwrap_or_else(|| panic!("no {}", markers[i])));
+        (grid, found)
+    }
+
     pub fn get<V: Into<Vector>>(&self, v: V) -> Option<&T> {
         let v: Vector = v.into();
         if 0 <= v.x && v.x < self.size.x && 0 <= v.y && v.y < self.size.y {
@@ -260,6 +310,14 @@ impl<T> Grid<T> {
     pub fn len(&self) -> usize {
         self.data.len()
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn map<U, F: FnMut(Vector, &T) -> U>(&self, mut f: F) -> Grid<U> {
+        Grid::from_iter(self.size, self.iter().map(|(k, v)| f(k, v)))
+    }
 }
 
 impl<T, V> Index<V> for Grid<T>
@@ -307,7 +365,8 @@ where
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+/// An axis aligned box, including both `min` and `max`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Bounds<const D: usize> {
     pub min: SVector<i32, D>,
     pub max: SVector<i32, D>,
@@ -338,6 +397,111 @@ impl<const D: usize> Bounds<D> {
     pub fn contains(&self, v: SVector<i32, D>) -> bool {
         self.min.iter().zip(&v).all(|(a, b)| a <= b) && self.max.iter().zip(&v).all(|(a, b)| a >= b)
     }
+
+    /// The number of lattice points in the box. An `i64` since this gets big fast in 3 dimensions.
+    pub fn volume(&self) -> i64 {
+        self.size().iter().map(|&len| i64::from(len)).product()
+    }
+
+    pub fn intersects(&self, other: &Self) -> bool {
+        (0..D).all(|axis| self.min[axis] <= other.max[axis] && other.min[axis] <= self.max[axis])
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        if self.intersects(other) {
+            Some(Self {
+                min: self.min.sup(&other.min),
+                max: self.max.inf(&other.max),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// The smallest box containing both `self` and `other`.
+    #[must_use]
+    pub fn hull(&self, other: &Self) -> Self {
+        Self {
+            min: self.min.inf(&other.min),
+            max: self.max.sup(&other.max),
+        }
+    }
+
+    /// Split the box in to the part below `at` on the given axis and the part at or above it.
+    /// Either part is `None` if it would be empty.
+    pub fn split(&self, axis: usize, at: i32) -> (Option<Self>, Option<Self>) {
+        if at <= self.min[axis] {
+            (None, Some(*self))
+        } else if at > self.max[axis] {
+            (Some(*self), None)
+        } else {
+            let mut below = *self;
+            let mut above = *self;
+            below.max[axis] = at - 1;
+            above.min[axis] = at;
+            (Some(below), Some(above))
+        }
+    }
+
+    /// The part of `self` outside `other`, as at most `2 * D` disjoint boxes.
+    pub fn difference(&self, other: &Self) -> Vec<Self> {
+        if !self.intersects(other) {
+            return vec![*self];
+        }
+        let mut res = Vec::new();
+        let mut rest = *self;
+        // Slice off the parts of rest below and above other on each axis in turn. What's left at
+        // the end is the intersection.
+        for axis in 0..D {
+            let (below, middle) = rest.split(axis, other.min[axis]);
+            res.extend(below);
+            let (middle, above) = middle.unwrap().split(axis, other.max[axis] + 1);
+            res.extend(above);
+            rest = middle.unwrap();
+        }
+        res
+    }
+
+    /// `self` and `other` together, as disjoint boxes.
+    pub fn union(&self, other: &Self) -> Vec<Self> {
+        let mut res = other.difference(self);
+        res.push(*self);
+        res
+    }
+}
+
+/// A set of lattice points represented as a collection of disjoint boxes, for when the points are
+/// too many to store individually.
+#[derive(Debug, Clone, Default)]
+pub struct BoxSet<const D: usize> {
+    boxes: Vec<Bounds<D>>,
+}
+
+impl<const D: usize> BoxSet<D> {
+    pub fn new() -> Self {
+        Self { boxes: Vec::new() }
+    }
+
+    pub fn insert(&mut self, b: Bounds<D>) {
+        self.remove(b);
+        self.boxes.push(b);
+    }
+
+    pub fn remove(&mut self, b: Bounds<D>) {
+        self.boxes = self.boxes.iter().flat_map(|a| a.difference(&b)).collect();
+    }
+
+    pub fn contains(&self, v: SVector<i32, D>) -> bool {
+        self.boxes.iter().any(|b| b.contains(v))
+    }
+
+    pub fn volume(&self) -> i64 {
+        self.boxes.iter().map(Bounds::volume).sum()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Bounds<D>> {
+        self.boxes.iter()
+    }
 }
 
 impl<I> From<I> for Grid<bool>
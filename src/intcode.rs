@@ -1,17 +1,32 @@
-use crate::unbounded_vec::UnboundedVec;
+use std::{collections::VecDeque, fmt};
 
-/// A virtual machine that runs Intcode. Memory is an unbounded vec of 64 bit signed integers. If
-/// you know what state a VM is in then call `input`, `output`, or `halt`, otherwise call `state`
-/// and match on the result.
+use asm::Op;
+use coverage::Coverage;
+use memory::{Decoded, Memory};
+use snapshot::Snapshot;
+
+pub mod asm;
+pub mod coverage;
+pub mod debug;
+pub mod memory;
+pub mod network;
+pub mod snapshot;
+
+/// A virtual machine that runs Intcode. Memory is unbounded, of 64 bit signed integers. If you know
+/// what state a VM is in then call `input`, `output`, or `halt`, otherwise call `state` and match on
+/// the result. Each of these panics if the program misbehaves; the `try_` variants return a
+/// `VmError` instead, for running programs which might not be well behaved.
 #[derive(Clone)]
 pub struct VM {
-    pub mem: UnboundedVec<i64>,
+    pub mem: Memory,
     ip: usize,
     base: i64,
+    budget: Option<u64>,
+    coverage: Option<Box<Coverage>>,
 }
 
 /// The three states that a VM can be in once it pauses.
-#[derive(PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum State {
     /// Waiting for a call to `input`.
     Input,
@@ -21,116 +36,457 @@ pub enum State {
     Halt,
 }
 
+/// Something going wrong while running a program, with where it went wrong.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct VmError {
+    pub kind: VmErrorKind,
+    /// The address of the faulting instruction.
+    pub ip: usize,
+    /// The whole instruction word, modes and all.
+    pub opcode: i64,
+    /// The address the instruction tried to access or jump to, if that was the problem.
+    pub addr: Option<i64>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VmErrorKind {
+    UnknownOpcode,
+    UnknownMode(i64),
+    NegativeAddress,
+    /// Called `input`, `output`, or `halt` when the VM was in another state.
+    WrongState {
+        expected: State,
+        actual: State,
+    },
+    /// Ran out of the instructions allowed by `set_budget`.
+    OutOfBudget,
+    /// Adding or multiplying these two operands overflowed. That includes working out an address,
+    /// from the relative base plus a parameter or the instruction pointer plus an offset.
+    Overflow(i64, i64),
+}
+
+impl fmt::Display for VmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            VmErrorKind::UnknownOpcode => write!(f, "unsupported opcode: {}", self.opcode % 100)?,
+            VmErrorKind::UnknownMode(mode) => write!(f, "unsupported mode: {mode}")?,
+            VmErrorKind::NegativeAddress => write!(f, "negative address: {}", self.addr.unwrap())?,
+            VmErrorKind::WrongState { expected, actual } => {
+                write!(f, "expected state {expected:?} but state is {actual:?}")?;
+            }
+            VmErrorKind::OutOfBudget => write!(f, "instruction budget exhausted")?,
+            VmErrorKind::Overflow(a, b) => write!(f, "overflow on operands {a} and {b}")?,
+        }
+        write!(f, " (ip {}, instruction {})", self.ip, self.opcode)
+    }
+}
+
+impl std::error::Error for VmError {}
+
+/// Parse a comma-separated Intcode program.
+pub fn parse(prog: &str) -> Vec<i64> {
+    prog.split(',').map(|s| s.trim().parse().unwrap()).collect()
+}
+
 impl VM {
     /// Construct a VM which will run the given Intcode program.
     pub fn new(prog: &str) -> Self {
         Self {
-            mem: UnboundedVec::new(prog.split(',').map(|s| s.parse().unwrap()).collect(), 0),
+            mem: Memory::new(&parse(prog)),
             ip: 0,
             base: 0,
+            budget: None,
+            coverage: None,
         }
     }
 
+    /// Only allow this many more instructions to be executed, after which everything returns
+    /// `OutOfBudget` errors. Stops programs which never reach the I/O we're expecting from looping
+    /// forever. Input and output instructions don't count, since they're driven by the caller, so
+    /// can't loop by themselves. `None` lifts the limit.
+    pub fn set_budget(&mut self, budget: Option<u64>) {
+        self.budget = budget;
+    }
+
+    /// How many more instructions are allowed, if there's a limit.
+    pub fn budget(&self) -> Option<u64> {
+        self.budget
+    }
+
+    /// Start counting which instructions run and which addresses they read and write, from
+    /// scratch. This slows the VM down a lot.
+    pub fn trace(&mut self) {
+        self.coverage = Some(Box::default());
+    }
+
+    /// What the VM has done since `trace` was called, if it was.
+    pub fn coverage(&self) -> Option<&Coverage> {
+        self.coverage.as_deref()
+    }
+
+    /// Capture the VM's state, to `restore` later or save to a file.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot::new(self.clone())
+    }
+
+    /// Go back to the state captured by a snapshot, which needn't have come from this VM.
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        self.clone_from(snapshot.vm());
+    }
+
     /// Drive the VM forward until it reaches an input, output, or halt instruction.
     pub fn state(&mut self) -> State {
+        self.try_state().unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    pub fn try_state(&mut self) -> Result<State, VmError> {
+        self.execute(false).map(Option::unwrap)
+    }
+
+    /// Execute a single instruction, unless it's an input, output, or halt instruction, in which
+    /// case return the state it leaves the VM in without executing it.
+    pub fn step(&mut self) -> Option<State> {
+        self.try_step().unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    pub fn try_step(&mut self) -> Result<Option<State>, VmError> {
+        self.execute(true)
+    }
+
+    /// Execute instructions until reaching an input, output, or halt instruction, or after the
+    /// first if `single`. The loop lives here rather than in `try_state` so that running doesn't
+    /// return a `Result` for every instruction, which costs more than the instruction itself.
+    fn execute(&mut self, single: bool) -> Result<Option<State>, VmError> {
         loop {
-            match self.mem[self.ip] % 100 {
+            let Some(d) = self.mem.decode(self.ip) else {
+                return Err(self.error(VmErrorKind::UnknownOpcode, None));
+            };
+            match d.op {
+                3 => return Ok(Some(State::Input)),
+                4 => return Ok(Some(State::Output)),
+                99 => return Ok(Some(State::Halt)),
+                _ => {}
+            }
+            if let Some(budget) = &mut self.budget {
+                if *budget == 0 {
+                    return Err(self.error(VmErrorKind::OutOfBudget, None));
+                }
+                *budget -= 1;
+            }
+            if self.coverage.is_some() {
+                self.record(d);
+            }
+            match d.op {
                 1 => {
-                    *self.arg(3) = *self.arg(1) + *self.arg(2);
-                    self.ip += 4;
+                    let (a, b) = (self.read(d, 1)?, self.read(d, 2)?);
+                    let sum = a.checked_add(b).ok_or_else(|| self.overflow(a, b))?;
+                    self.write(d, 3, sum)?;
+                    self.advance(4)?;
                 }
                 2 => {
-                    *self.arg(3) = *self.arg(1) * *self.arg(2);
-                    self.ip += 4;
-                }
-                3 => {
-                    return State::Input;
-                }
-                4 => {
-                    return State::Output;
+                    let (a, b) = (self.read(d, 1)?, self.read(d, 2)?);
+                    let product = a.checked_mul(b).ok_or_else(|| self.overflow(a, b))?;
+                    self.write(d, 3, product)?;
+                    self.advance(4)?;
                 }
                 5 => {
-                    if *self.arg(1) == 0 {
-                        self.ip += 3;
+                    if self.read(d, 1)? == 0 {
+                        self.advance(3)?;
                     } else {
-                        self.ip = usize::try_from(*self.arg(2)).unwrap();
+                        self.ip = self.jump_target(d)?;
                     }
                 }
                 6 => {
-                    if *self.arg(1) == 0 {
-                        self.ip = usize::try_from(*self.arg(2)).unwrap();
+                    if self.read(d, 1)? == 0 {
+                        self.ip = self.jump_target(d)?;
                     } else {
-                        self.ip += 3;
+                        self.advance(3)?;
                     }
                 }
                 7 => {
-                    *self.arg(3) = (*self.arg(1) < *self.arg(2)).into();
-                    self.ip += 4;
+                    self.write(d, 3, (self.read(d, 1)? < self.read(d, 2)?).into())?;
+                    self.advance(4)?;
                 }
                 8 => {
-                    *self.arg(3) = (*self.arg(1) == *self.arg(2)).into();
-                    self.ip += 4;
+                    self.write(d, 3, (self.read(d, 1)? == self.read(d, 2)?).into())?;
+                    self.advance(4)?;
                 }
                 9 => {
-                    self.base += *self.arg(1);
-                    self.ip += 2;
-                }
-                99 => {
-                    return State::Halt;
-                }
-                op => {
-                    panic!("unsupported opcode: {op}");
+                    let offset = self.read(d, 1)?;
+                    self.base = self
+                        .base
+                        .checked_add(offset)
+                        .ok_or_else(|| self.overflow(self.base, offset))?;
+                    self.advance(2)?;
                 }
+                _ => unreachable!(),
+            }
+            if single {
+                return Ok(None);
             }
         }
     }
 
+    /// The instruction pointer.
+    pub fn ip(&self) -> usize {
+        self.ip
+    }
+
+    /// The relative base.
+    pub fn base(&self) -> i64 {
+        self.base
+    }
+
     /// Input a value. Panic if the VM is not in `Input` state.
     pub fn input(&mut self, input: i64) {
-        match self.state() {
-            State::Input => {
-                *self.arg(1) = input;
-                self.ip += 2;
-            }
-            state => panic!("can't input when state is {state:?}"),
-        }
+        self.try_input(input).unwrap_or_else(|err| panic!("{err}"));
+    }
+
+    pub fn try_input(&mut self, input: i64) -> Result<(), VmError> {
+        self.expect(State::Input)?;
+        let d = self.decoded();
+        self.record(d);
+        self.write(d, 1, input)?;
+        self.advance(2)?;
+        Ok(())
     }
 
     /// Output a value. Panic if the VM is not in `Output` state.
     pub fn output(&mut self) -> i64 {
-        match self.state() {
-            State::Output => {
-                let output = *self.arg(1);
-                self.ip += 2;
-                output
-            }
-            state => panic!("can't output when state is {state:?}"),
-        }
+        self.try_output().unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    pub fn try_output(&mut self) -> Result<i64, VmError> {
+        self.expect(State::Output)?;
+        let d = self.decoded();
+        self.record(d);
+        let output = self.read(d, 1)?;
+        self.advance(2)?;
+        Ok(output)
     }
 
     /// Halt. Panic if the VM is not in `Halt` state. Good practice to call this when done to ensure
     /// no outputs are missed.
     pub fn halt(&mut self) {
-        match self.state() {
-            State::Halt => {}
-            state => panic!("can't halt when state is {state:?}"),
+        self.try_halt().unwrap_or_else(|err| panic!("{err}"));
+    }
+
+    pub fn try_halt(&mut self) -> Result<(), VmError> {
+        self.expect(State::Halt)
+    }
+
+    /// The value the VM is waiting to output, without consuming it, or `None` if it isn't waiting
+    /// to output anything.
+    pub fn peek(&mut self) -> Option<i64> {
+        (self.state() == State::Output).then(|| {
+            let d = self.decoded();
+            self.read(d, 1).unwrap()
+        })
+    }
+
+    /// An iterator over the VM's outputs, which ends when it needs input or halts.
+    pub fn outputs(&mut self) -> Outputs<'_> {
+        Outputs(self)
+    }
+
+    /// Run until the VM halts or asks for more input than `input` has, returning everything it
+    /// output.
+    pub fn run_with(&mut self, input: impl IntoIterator<Item = i64>) -> Vec<i64> {
+        let mut input = input.into_iter();
+        let mut output = Vec::new();
+        loop {
+            match self.state() {
+                State::Input => match input.next() {
+                    Some(value) => self.input(value),
+                    None => return output,
+                },
+                State::Output => output.push(self.output()),
+                State::Halt => return output,
+            }
         }
     }
 
-    /// Get the value of the argument at offset `n` from the instruction pointer according to the
-    /// specified mode.
-    fn arg(&mut self, n: usize) -> &mut i64 {
-        match self.mem[self.ip] / 10_i64.pow(1 + u32::try_from(n).unwrap()) % 10 {
-            0 => {
-                let i = usize::try_from(self.mem[self.ip + n]).unwrap();
-                &mut self.mem[i]
+    /// Run until the VM halts, or needs input when `input` is empty, feeding it input from the
+    /// front of the queue and collecting its output. Returns the state it stopped in, which is
+    /// either `Input` or `Halt`. This is the building block for running several VMs which talk to
+    /// each other, one at a time.
+    pub fn run_until_blocked(
+        &mut self,
+        input: &mut VecDeque<i64>,
+        output: &mut impl Extend<i64>,
+    ) -> State {
+        loop {
+            match self.state() {
+                State::Input => match input.pop_front() {
+                    Some(value) => self.input(value),
+                    None => return State::Input,
+                },
+                State::Output => output.extend(Some(self.output())),
+                State::Halt => return State::Halt,
             }
-            1 => &mut self.mem[self.ip + n],
-            2 => {
-                let i = usize::try_from(self.mem[self.ip + n] + self.base).unwrap();
-                &mut self.mem[i]
+        }
+    }
+
+    /// Input a line of ASCII text, followed by a newline.
+    pub fn send_line(&mut self, line: &str) {
+        assert!(line.is_ascii(), "can't send non-ASCII text: {line}");
+        for b in line.bytes().chain(Some(b'\n')) {
+            self.input(b.into());
+        }
+    }
+
+    /// Output a line of ASCII text, without its newline. Stops short of the newline if the VM
+    /// stops outputting ASCII before then, and returns `None` if it isn't outputting ASCII at all.
+    pub fn recv_line(&mut self) -> Option<String> {
+        let mut line = String::new();
+        while let Some(c) = self.peek().and_then(ascii) {
+            self.output();
+            if c == '\n' {
+                return Some(line);
             }
-            m => panic!("unsupported mode: {m}"),
+            line.push(c);
+        }
+        (!line.is_empty()).then_some(line)
+    }
+
+    /// Output ASCII text until the VM needs input, halts, or outputs something which isn't ASCII.
+    /// The ASCII days output their final answer as a number too big to be a character, and this
+    /// leaves it for `output`.
+    pub fn recv_text(&mut self) -> String {
+        let mut text = String::new();
+        while let Some(c) = self.peek().and_then(ascii) {
+            self.output();
+            text.push(c);
+        }
+        text
+    }
+
+    fn expect(&mut self, expected: State) -> Result<(), VmError> {
+        let actual = self.try_state()?;
+        if actual == expected {
+            Ok(())
+        } else {
+            Err(self.error(VmErrorKind::WrongState { expected, actual }, None))
+        }
+    }
+
+    /// Count the instruction about to be executed, and the addresses it reads and writes, if
+    /// tracing.
+    fn record(&mut self, d: Decoded) {
+        if self.coverage.is_none() {
+            return;
         }
+        let op = Op::from_opcode(d.op.into()).unwrap();
+        let accesses: Vec<(usize, bool)> = (1..=op.arity())
+            .filter(|&n| d.modes[n - 1] != 1)
+            .filter_map(|n| Some((self.addr(d, n).ok()?, op.output() == Some(n - 1))))
+            .collect();
+        let coverage = self.coverage.as_mut().unwrap();
+        *coverage.executed.entry(self.ip).or_default() += 1;
+        for (addr, write) in accesses {
+            let counts = if write {
+                &mut coverage.writes
+            } else {
+                &mut coverage.reads
+            };
+            *counts.entry(addr).or_default() += 1;
+        }
+    }
+
+    /// The instruction at the instruction pointer, which is known to be valid.
+    fn decoded(&self) -> Decoded {
+        self.mem.decode(self.ip).unwrap()
+    }
+
+    #[cold]
+    fn error(&self, kind: VmErrorKind, addr: Option<i64>) -> VmError {
+        VmError {
+            kind,
+            ip: self.ip,
+            opcode: self.mem[self.ip],
+            addr,
+        }
+    }
+
+    #[cold]
+    fn overflow(&self, a: i64, b: i64) -> VmError {
+        self.error(VmErrorKind::Overflow(a, b), None)
+    }
+
+    /// Move the instruction pointer on past an instruction of `size` words.
+    ///
+    /// This and the parameter helpers below are forced inline, since left to itself the compiler
+    /// doesn't inline them all into `execute`, and passing their `Result`s back then costs more
+    /// than the instructions do. Benchmarking shows the difference is about a factor of two.
+    #[expect(clippy::inline_always)]
+    #[inline(always)]
+    fn advance(&mut self, size: usize) -> Result<(), VmError> {
+        self.ip = self.offset(size)?;
+        Ok(())
+    }
+
+    /// The address `n` words on from the instruction pointer.
+    #[expect(clippy::inline_always)]
+    #[inline(always)]
+    fn offset(&self, n: usize) -> Result<usize, VmError> {
+        self.ip.checked_add(n).ok_or_else(|| {
+            let ip = i64::try_from(self.ip).unwrap_or(i64::MAX);
+            self.overflow(ip, i64::try_from(n).unwrap())
+        })
+    }
+
+    fn jump_target(&self, d: Decoded) -> Result<usize, VmError> {
+        let target = self.read(d, 2)?;
+        usize::try_from(target).map_err(|_| self.error(VmErrorKind::NegativeAddress, Some(target)))
+    }
+
+    /// The address of the parameter at offset `n` from the instruction pointer according to the
+    /// specified mode. For immediate mode that's the parameter itself.
+    #[expect(clippy::inline_always)]
+    #[inline(always)]
+    fn addr(&self, d: Decoded, n: usize) -> Result<usize, VmError> {
+        let at = self.offset(n)?;
+        let param = self.mem[at];
+        let addr = match d.modes[n - 1] {
+            0 => param,
+            1 => return Ok(at),
+            2 => param
+                .checked_add(self.base)
+                .ok_or_else(|| self.overflow(param, self.base))?,
+            m => return Err(self.error(VmErrorKind::UnknownMode(m.into()), None)),
+        };
+        usize::try_from(addr).map_err(|_| self.error(VmErrorKind::NegativeAddress, Some(addr)))
+    }
+
+    #[expect(clippy::inline_always)]
+    #[inline(always)]
+    fn read(&self, d: Decoded, n: usize) -> Result<i64, VmError> {
+        Ok(self.mem[self.addr(d, n)?])
+    }
+
+    #[expect(clippy::inline_always)]
+    #[inline(always)]
+    fn write(&mut self, d: Decoded, n: usize, value: i64) -> Result<(), VmError> {
+        let addr = self.addr(d, n)?;
+        self.mem[addr] = value;
+        Ok(())
+    }
+}
+
+fn ascii(value: i64) -> Option<char> {
+    u8::try_from(value)
+        .ok()
+        .filter(u8::is_ascii)
+        .map(char::from)
+}
+
+/// See `VM::outputs`.
+pub struct Outputs<'a>(&'a mut VM);
+
+impl Iterator for Outputs<'_> {
+    type Item = i64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        (self.0.state() == State::Output).then(|| self.0.output())
     }
 }
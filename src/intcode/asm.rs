@@ -0,0 +1,487 @@
+//! A textual syntax for Intcode, with a disassembler for reading puzzle programs and an assembler
+//! for writing test programs. Each instruction is a mnemonic followed by its parameters in memory
+//! order, so `add [r+3], #5, [12]` adds the value at the relative base plus three to five, and
+//! stores the result at address twelve. `#n` is an immediate, `[n]` a position, and `[r+n]` or
+//! `[r-n]` relative to the base. Labels are written `name:` and can stand in for any number, and
+//! `data 1, 2, 3` lays out raw values. Comments run from `;` to the end of the line.
+
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    fmt::{self, Write},
+    ops::Range,
+};
+
+/// An Intcode operation.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Op {
+    Add,
+    Mul,
+    In,
+    Out,
+    JumpIfTrue,
+    JumpIfFalse,
+    LessThan,
+    Equals,
+    AdjustBase,
+    Halt,
+}
+
+const OPS: [Op; 10] = [
+    Op::Add,
+    Op::Mul,
+    Op::In,
+    Op::Out,
+    Op::JumpIfTrue,
+    Op::JumpIfFalse,
+    Op::LessThan,
+    Op::Equals,
+    Op::AdjustBase,
+    Op::Halt,
+];
+
+impl Op {
+    pub fn opcode(self) -> i64 {
+        match self {
+            Op::Add => 1,
+            Op::Mul => 2,
+            Op::In => 3,
+            Op::Out => 4,
+            Op::JumpIfTrue => 5,
+            Op::JumpIfFalse => 6,
+            Op::LessThan => 7,
+            Op::Equals => 8,
+            Op::AdjustBase => 9,
+            Op::Halt => 99,
+        }
+    }
+
+    pub fn from_opcode(opcode: i64) -> Option<Self> {
+        OPS.into_iter().find(|op| op.opcode() == opcode)
+    }
+
+    pub fn mnemonic(self) -> &'static str {
+        match self {
+            Op::Add => "add",
+            Op::Mul => "mul",
+            Op::In => "in",
+            Op::Out => "out",
+            Op::JumpIfTrue => "jt",
+            Op::JumpIfFalse => "jf",
+            Op::LessThan => "lt",
+            Op::Equals => "eq",
+            Op::AdjustBase => "arb",
+            Op::Halt => "hlt",
+        }
+    }
+
+    pub fn from_mnemonic(mnemonic: &str) -> Option<Self> {
+        OPS.into_iter().find(|op| op.mnemonic() == mnemonic)
+    }
+
+    /// The number of parameters following the opcode.
+    pub fn arity(self) -> usize {
+        match self {
+            Op::Add | Op::Mul | Op::LessThan | Op::Equals => 3,
+            Op::JumpIfTrue | Op::JumpIfFalse => 2,
+            Op::In | Op::Out | Op::AdjustBase => 1,
+            Op::Halt => 0,
+        }
+    }
+
+    /// The index of the parameter this operation writes to, if any.
+    pub fn output(self) -> Option<usize> {
+        match self {
+            Op::Add | Op::Mul | Op::LessThan | Op::Equals => Some(2),
+            Op::In => Some(0),
+            _ => None,
+        }
+    }
+}
+
+/// How a parameter's value is interpreted.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Mode {
+    Position,
+    Immediate,
+    Relative,
+}
+
+impl Mode {
+    fn from_digit(digit: i64) -> Option<Self> {
+        match digit {
+            0 => Some(Mode::Position),
+            1 => Some(Mode::Immediate),
+            2 => Some(Mode::Relative),
+            _ => None,
+        }
+    }
+
+    fn digit(self) -> i64 {
+        match self {
+            Mode::Position => 0,
+            Mode::Immediate => 1,
+            Mode::Relative => 2,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Param {
+    pub mode: Mode,
+    pub value: i64,
+}
+
+impl fmt::Display for Param {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.mode {
+            Mode::Position => write!(f, "[{}]", self.value),
+            Mode::Immediate => write!(f, "#{}", self.value),
+            Mode::Relative if self.value == 0 => write!(f, "[r]"),
+            Mode::Relative => write!(f, "[r{:+}]", self.value),
+        }
+    }
+}
+
+/// A decoded instruction.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Instruction {
+    pub op: Op,
+    pub params: Vec<Param>,
+}
+
+impl Instruction {
+    /// Decode the instruction at `addr`, or `None` if the opcode or any of the modes is invalid.
+    pub fn decode(mem: &[i64], addr: usize) -> Option<Self> {
+        let word = *mem.get(addr)?;
+        if word < 0 {
+            return None;
+        }
+        let op = Op::from_opcode(word % 100)?;
+        let mut modes = word / 100;
+        let mut params = Vec::with_capacity(op.arity());
+        for i in 0..op.arity() {
+            params.push(Param {
+                mode: Mode::from_digit(modes % 10)?,
+                value: *mem.get(addr + 1 + i)?,
+            });
+            modes /= 10;
+        }
+        // Leftover mode digits would be ignored by the VM, but no sane program has them, so the
+        // word is more likely to be data.
+        (modes == 0).then_some(Self { op, params })
+    }
+
+    /// The number of memory cells the instruction occupies.
+    pub fn size(&self) -> usize {
+        1 + self.params.len()
+    }
+
+    pub fn encode(&self) -> Vec<i64> {
+        let modes = self
+            .params
+            .iter()
+            .rev()
+            .fold(0, |modes, param| modes * 10 + param.mode.digit());
+        let mut words = vec![modes * 100 + self.op.opcode()];
+        words.extend(self.params.iter().map(|param| param.value));
+        words
+    }
+
+    /// The immediate address this instruction may jump to, if it's a jump.
+    fn target(&self) -> Option<usize> {
+        match self.op {
+            Op::JumpIfTrue | Op::JumpIfFalse if self.params[1].mode == Mode::Immediate => {
+                usize::try_from(self.params[1].value).ok()
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether execution can carry on to the next instruction.
+    fn falls_through(&self) -> bool {
+        match self.op {
+            Op::Halt => false,
+            Op::JumpIfTrue => {
+                !(self.params[0].mode == Mode::Immediate && self.params[0].value != 0)
+            }
+            Op::JumpIfFalse => {
+                !(self.params[0].mode == Mode::Immediate && self.params[0].value == 0)
+            }
+            _ => true,
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.op.mnemonic())?;
+        for (i, param) in self.params.iter().enumerate() {
+            write!(f, "{}{param}", if i == 0 { " " } else { ", " })?;
+        }
+        Ok(())
+    }
+}
+
+/// Finds the instructions reachable from address zero. Jumps through memory can't be followed
+/// statically, but compiled programs only use them to return from calls, and a call pushes its
+/// return address as an immediate before jumping away unconditionally. So an immediate that
+/// points just past a reachable unconditional jump is taken to be reachable too. Self-modifying
+/// code will fool this.
+pub fn reachable(mem: &[i64]) -> HashMap<usize, Instruction> {
+    let mut code = HashMap::new();
+    let mut seen = HashSet::new();
+    let mut dead_ends = HashSet::new();
+    let mut todo = vec![0];
+    loop {
+        while let Some(addr) = todo.pop() {
+            if !seen.insert(addr) {
+                continue;
+            }
+            let Some(instruction) = Instruction::decode(mem, addr) else {
+                continue;
+            };
+            if instruction.falls_through() {
+                todo.push(addr + instruction.size());
+            } else {
+                dead_ends.insert(addr + instruction.size());
+            }
+            todo.extend(instruction.target());
+            code.insert(addr, instruction);
+        }
+        todo.extend(
+            code.values()
+                .flat_map(|instruction| &instruction.params)
+                .filter(|param| param.mode == Mode::Immediate)
+                .filter_map(|param| usize::try_from(param.value).ok())
+                .filter(|addr| dead_ends.contains(addr) && !seen.contains(addr)),
+        );
+        if todo.is_empty() {
+            return code;
+        }
+    }
+}
+
+/// A listing of the program, with code told apart from data by `reachable`. Jump targets and
+/// return addresses are labelled, and the address of each line is given in a comment. A target in
+/// the middle of another instruction (code that jumps in to its own operands, say) has no line to
+/// label, so it's left as a number. The listing assembles back into the same program.
+pub fn disassemble(mem: &[i64]) -> String {
+    disassemble_with(mem, |addrs| addrs.start.to_string())
+}
+
+/// Like `disassemble`, but with each line's comment given by `comment`, from the range of addresses
+/// that the line lays out.
+pub fn disassemble_with(mem: &[i64], mut comment: impl FnMut(Range<usize>) -> String) -> String {
+    let code = reachable(mem);
+    let return_addresses: HashSet<usize> = code
+        .iter()
+        .filter(|(_, instruction)| !instruction.falls_through())
+        .map(|(addr, instruction)| addr + instruction.size())
+        .filter(|addr| code.contains_key(addr))
+        .collect();
+    // The addresses the listing starts a line at, which are the only ones that can be labelled.
+    let mut starts = HashSet::new();
+    let mut addr = 0;
+    while addr < mem.len() {
+        starts.insert(addr);
+        addr += code.get(&addr).map_or(1, Instruction::size);
+    }
+    let labels: BTreeSet<usize> = code
+        .values()
+        .flat_map(|instruction| {
+            instruction.target().into_iter().chain(
+                instruction
+                    .params
+                    .iter()
+                    .filter(|param| param.mode == Mode::Immediate)
+                    .filter_map(|param| usize::try_from(param.value).ok())
+                    .filter(|addr| return_addresses.contains(addr)),
+            )
+        })
+        .filter(|addr| starts.contains(addr))
+        .collect();
+    let label = |param: &Param| {
+        usize::try_from(param.value)
+            .ok()
+            .filter(|addr| param.mode == Mode::Immediate && labels.contains(addr))
+            .map(|addr| format!("#L{addr}"))
+    };
+
+    let mut listing = String::new();
+    let mut data = Vec::new();
+    let mut addr = 0;
+    while addr < mem.len() {
+        if labels.contains(&addr) || code.contains_key(&addr) || data.len() == 8 {
+            flush_data(&mut listing, &mut data, addr, &mut comment);
+        }
+        if labels.contains(&addr) {
+            writeln!(listing, "L{addr}:").unwrap();
+        }
+        if let Some(instruction) = code.get(&addr) {
+            let mut line = instruction.op.mnemonic().to_string();
+            for (i, param) in instruction.params.iter().enumerate() {
+                line += if i == 0 { " " } else { ", " };
+                line += &label(param).unwrap_or_else(|| param.to_string());
+            }
+            let end = addr + instruction.size();
+            writeln!(listing, "    {line:<32}; {}", comment(addr..end)).unwrap();
+            addr = end;
+        } else {
+            data.push(mem[addr]);
+            addr += 1;
+        }
+    }
+    flush_data(&mut listing, &mut data, addr, &mut comment);
+    listing
+}
+
+fn flush_data(
+    listing: &mut String,
+    data: &mut Vec<i64>,
+    end: usize,
+    comment: &mut impl FnMut(Range<usize>) -> String,
+) {
+    if !data.is_empty() {
+        let values: Vec<String> = data.iter().map(ToString::to_string).collect();
+        let line = format!("data {}", values.join(", "));
+        let start = end - data.len();
+        writeln!(listing, "    {line:<32}; {}", comment(start..end)).unwrap();
+        data.clear();
+    }
+}
+
+/// Assemble a listing in the syntax described at the top of this module into a comma-separated
+/// program for `VM::new`. Errors give the line number and what was wrong with it.
+pub fn assemble(source: &str) -> Result<String, String> {
+    // The first pass just works out the address of each label.
+    let mut labels = HashMap::new();
+    let mut addr = 0;
+    for (n, line) in source.lines().enumerate() {
+        let (label, rest) = split_label(line);
+        if let Some(label) = label
+            && labels.insert(label, addr).is_some()
+        {
+            return Err(format!("line {}: duplicate label {label}", n + 1));
+        }
+        if let Some((mnemonic, operands)) = split_mnemonic(rest) {
+            addr += if mnemonic == "data" {
+                operands.len()
+            } else {
+                operands.len() + 1
+            };
+        }
+    }
+
+    let mut words = Vec::new();
+    for (n, line) in source.lines().enumerate() {
+        let (_, rest) = split_label(line);
+        if let Some((mnemonic, operands)) = split_mnemonic(rest) {
+            assemble_line(mnemonic, &operands, &labels, &mut words)
+                .map_err(|err| format!("line {}: {err}", n + 1))?;
+        }
+    }
+    let words: Vec<String> = words.iter().map(ToString::to_string).collect();
+    Ok(words.join(","))
+}
+
+fn split_label(line: &str) -> (Option<&str>, &str) {
+    let line = line.split(';').next().unwrap().trim();
+    match line.split_once(':') {
+        Some((label, rest)) => (Some(label.trim()), rest.trim()),
+        None => (None, line),
+    }
+}
+
+fn split_mnemonic(line: &str) -> Option<(&str, Vec<&str>)> {
+    if line.is_empty() {
+        return None;
+    }
+    let (mnemonic, operands) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let operands = operands
+        .split(',')
+        .map(str::trim)
+        .filter(|operand| !operand.is_empty())
+        .collect();
+    Some((mnemonic, operands))
+}
+
+fn assemble_line(
+    mnemonic: &str,
+    operands: &[&str],
+    labels: &HashMap<&str, usize>,
+    words: &mut Vec<i64>,
+) -> Result<(), String> {
+    if mnemonic == "data" {
+        for operand in operands {
+            words.push(value(operand, labels)?);
+        }
+        return Ok(());
+    }
+    let op = Op::from_mnemonic(mnemonic).ok_or(format!("unknown mnemonic {mnemonic}"))?;
+    if operands.len() != op.arity() {
+        return Err(format!(
+            "{mnemonic} takes {} parameters, not {}",
+            op.arity(),
+            operands.len()
+        ));
+    }
+    let params = operands
+        .iter()
+        .map(|operand| param(operand, labels))
+        .collect::<Result<Vec<_>, _>>()?;
+    if let Some(i) = op.output()
+        && params[i].mode == Mode::Immediate
+    {
+        return Err(format!("{mnemonic} can't write to an immediate"));
+    }
+    words.extend(Instruction { op, params }.encode());
+    Ok(())
+}
+
+fn param(operand: &str, labels: &HashMap<&str, usize>) -> Result<Param, String> {
+    if let Some(value) = operand.strip_prefix('#') {
+        return Ok(Param {
+            mode: Mode::Immediate,
+            value: self::value(value, labels)?,
+        });
+    }
+    let inner = operand
+        .strip_prefix('[')
+        .and_then(|operand| operand.strip_suffix(']'))
+        .ok_or(format!("bad parameter {operand}"))?
+        .trim();
+    let Some(offset) = inner
+        .strip_prefix('r')
+        .filter(|offset| offset.is_empty() || offset.trim_start().starts_with(['+', '-']))
+    else {
+        return Ok(Param {
+            mode: Mode::Position,
+            value: value(inner, labels)?,
+        });
+    };
+    let offset = offset.trim();
+    let value = if offset.is_empty() {
+        0
+    } else if let Some(offset) = offset.strip_prefix('+') {
+        value(offset, labels)?
+    } else if let Some(offset) = offset.strip_prefix('-') {
+        -value(offset, labels)?
+    } else {
+        return Err(format!("bad parameter {operand}"));
+    };
+    Ok(Param {
+        mode: Mode::Relative,
+        value,
+    })
+}
+
+fn value(operand: &str, labels: &HashMap<&str, usize>) -> Result<i64, String> {
+    let operand = operand.trim();
+    if let Ok(value) = operand.parse() {
+        Ok(value)
+    } else if let Some(&addr) = labels.get(operand) {
+        Ok(i64::try_from(addr).unwrap())
+    } else {
+        Err(format!("unknown label {operand}"))
+    }
+}
@@ -0,0 +1,53 @@
+use std::{collections::HashMap, ops::Range};
+
+use super::asm;
+
+/// What a VM did while it was being traced: how many times the instruction at each address was
+/// executed, and how many times each address was read or written by an instruction's parameters.
+#[derive(Clone, Default, Debug)]
+pub struct Coverage {
+    pub executed: HashMap<usize, u64>,
+    pub reads: HashMap<usize, u64>,
+    pub writes: HashMap<usize, u64>,
+}
+
+impl Coverage {
+    /// The addresses of the `n` most executed instructions, with how many times each ran, most
+    /// first.
+    pub fn hottest(&self, n: usize) -> Vec<(usize, u64)> {
+        let mut hottest: Vec<(usize, u64)> = self.executed.iter().map(|(&a, &c)| (a, c)).collect();
+        hottest.sort_unstable_by_key(|&(addr, count)| (u64::MAX - count, addr));
+        hottest.truncate(n);
+        hottest
+    }
+
+    /// A disassembly of `mem`, as from `asm::disassemble`, with the comment on each line giving
+    /// its address and what happened there: how many times each instruction ran, and how many
+    /// times data was read and written. Lines that were used also get a bar, its length the number
+    /// of digits in the count, so that hot loops stand out. The listing still assembles.
+    pub fn report(&self, mem: &[i64]) -> String {
+        let total = |counts: &HashMap<usize, u64>, addrs: &Range<usize>| -> u64 {
+            addrs.clone().filter_map(|addr| counts.get(&addr)).sum()
+        };
+        asm::disassemble_with(mem, |addrs| {
+            let start = addrs.start;
+            if let Some(&count) = self.executed.get(&start) {
+                return format!("{start:<6} {count:>10} {}", bar(count));
+            }
+            let (reads, writes) = (total(&self.reads, &addrs), total(&self.writes, &addrs));
+            if reads + writes == 0 {
+                return start.to_string();
+            }
+            let counts = format!("r{reads} w{writes}");
+            format!("{start:<6} {counts:>10} {}", bar(reads + writes))
+        })
+    }
+}
+
+fn bar(count: u64) -> String {
+    "#".repeat(
+        count
+            .checked_ilog10()
+            .map_or(0, |digits| digits as usize + 1),
+    )
+}
@@ -0,0 +1,307 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt,
+    hash::Hash,
+    io::{self, BufRead, Write},
+};
+
+use super::{
+    State, VM, VmError,
+    asm::{Instruction, Mode, Op},
+};
+
+/// Why the debugger stopped.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Stop {
+    /// About to execute the instruction at a breakpoint address.
+    Breakpoint(usize),
+    /// About to execute an instruction with a breakpoint on its operation.
+    OpBreakpoint(Op),
+    /// Just wrote to a watched address.
+    Watchpoint { addr: usize, old: i64, new: i64 },
+    /// About to execute an input instruction, with no input queued.
+    Input,
+    /// About to execute a halt instruction.
+    Halt,
+    /// The instruction at the instruction pointer can't be executed.
+    Error(VmError),
+}
+
+impl fmt::Display for Stop {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Stop::Breakpoint(addr) => write!(f, "breakpoint at {addr}"),
+            Stop::OpBreakpoint(op) => write!(f, "breakpoint on {}", op.mnemonic()),
+            Stop::Watchpoint { addr, old, new } => write!(f, "[{addr}] {old} -> {new}"),
+            Stop::Input => write!(f, "waiting for input"),
+            Stop::Halt => write!(f, "halted"),
+            Stop::Error(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+/// An instruction as it was executed.
+#[derive(Clone, Debug)]
+pub struct TraceEntry {
+    pub ip: usize,
+    pub base: i64,
+    pub instruction: Instruction,
+}
+
+impl fmt::Display for TraceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:>6} r={:<6} {}", self.ip, self.base, self.instruction)
+    }
+}
+
+/// Wraps a VM to execute it one instruction at a time, stopping at breakpoints and watchpoints.
+/// Input is fed from a queue rather than through `VM::input`, and output collected likewise, so
+/// that stepping can go straight through I/O instructions.
+pub struct Debugger {
+    pub vm: VM,
+    pub breakpoints: HashSet<usize>,
+    pub op_breakpoints: HashSet<Op>,
+    pub watchpoints: HashSet<usize>,
+    pub input: VecDeque<i64>,
+    pub output: Vec<i64>,
+    /// Every instruction executed while `tracing` is on.
+    pub trace: Vec<TraceEntry>,
+    pub tracing: bool,
+}
+
+impl Debugger {
+    pub fn new(vm: VM) -> Self {
+        Self {
+            vm,
+            breakpoints: HashSet::new(),
+            op_breakpoints: HashSet::new(),
+            watchpoints: HashSet::new(),
+            input: VecDeque::new(),
+            output: Vec::new(),
+            trace: Vec::new(),
+            tracing: false,
+        }
+    }
+
+    /// The instruction at the instruction pointer, or `None` if it doesn't decode.
+    pub fn instruction(&self) -> Option<Instruction> {
+        let ip = self.vm.ip();
+        let window: Vec<i64> = (ip..ip + 4).map(|i| self.vm.mem[i]).collect();
+        Instruction::decode(&window, 0)
+    }
+
+    /// The address the current instruction will write to, if it writes to memory.
+    fn write_addr(&self, instruction: &Instruction) -> Option<usize> {
+        let param = instruction.params[instruction.op.output()?];
+        match param.mode {
+            Mode::Position => usize::try_from(param.value).ok(),
+            Mode::Relative => usize::try_from(param.value.checked_add(self.vm.base())?).ok(),
+            Mode::Immediate => None,
+        }
+    }
+
+    /// Execute a single instruction, including input and output instructions, but stopping
+    /// instead of executing a halt, an input with nothing queued, or a faulty instruction. Also
+    /// stops after writing to a watched address. Breakpoints are ignored.
+    pub fn step(&mut self) -> Option<Stop> {
+        let instruction = self.instruction();
+        let watched = instruction
+            .as_ref()
+            .and_then(|instruction| self.write_addr(instruction))
+            .filter(|addr| self.watchpoints.contains(addr));
+        let old = watched.map(|addr| self.vm.mem[addr]);
+        let entry = instruction.map(|instruction| TraceEntry {
+            ip: self.vm.ip(),
+            base: self.vm.base(),
+            instruction,
+        });
+
+        let res = match self.vm.try_step() {
+            Ok(Some(State::Input)) => match self.input.pop_front() {
+                Some(value) => self.vm.try_input(value),
+                None => return Some(Stop::Input),
+            },
+            Ok(Some(State::Output)) => self.vm.try_output().map(|value| self.output.push(value)),
+            Ok(Some(State::Halt)) => return Some(Stop::Halt),
+            Ok(None) => Ok(()),
+            Err(err) => Err(err),
+        };
+        if let Err(err) = res {
+            return Some(Stop::Error(err));
+        }
+
+        if self.tracing {
+            self.trace.extend(entry);
+        }
+        let addr = watched?;
+        Some(Stop::Watchpoint {
+            addr,
+            old: old.unwrap(),
+            new: self.vm.mem[addr],
+        })
+    }
+
+    /// Step until something stops us. Breakpoints are checked before each instruction other than
+    /// the first, so that calling this again carries on from a breakpoint.
+    pub fn run(&mut self) -> Stop {
+        if let Some(stop) = self.step() {
+            return stop;
+        }
+        loop {
+            if let Some(stop) = self.breakpoint() {
+                return stop;
+            }
+            if let Some(stop) = self.step() {
+                return stop;
+            }
+        }
+    }
+
+    fn breakpoint(&self) -> Option<Stop> {
+        let ip = self.vm.ip();
+        if self.breakpoints.contains(&ip) {
+            return Some(Stop::Breakpoint(ip));
+        }
+        let op = Op::from_opcode(self.vm.mem[ip] % 100)?;
+        self.op_breakpoints
+            .contains(&op)
+            .then_some(Stop::OpBreakpoint(op))
+    }
+}
+
+const HELP: &str = "\
+s [n]            step n instructions (default 1)
+c                continue until something stops us
+b <addr|op>      toggle a breakpoint on an address or an operation (like jt)
+w <addr>         toggle a watchpoint on writes to an address
+i <n>... | \"s\"   queue numbers, or a line of ASCII text, as input
+m <addr> [n]     show n words of memory (default 8, at most 256)
+r                show registers and the current instruction
+t                toggle tracing, and show the trace so far
+q                quit
+";
+
+/// An interactive debugger reading commands from `input` and writing to `output`. Output from the
+/// VM is shown as it's produced, as text when it's printable ASCII.
+pub fn repl(
+    debugger: &mut Debugger,
+    input: impl BufRead,
+    mut output: impl Write,
+) -> io::Result<()> {
+    write!(output, "{HELP}> ")?;
+    output.flush()?;
+    for line in input.lines() {
+        let line = line?;
+        let mut words = line.split_whitespace();
+        match (words.next(), words.next()) {
+            (Some("s"), n) => {
+                for _ in 0..n.and_then(|n| n.parse().ok()).unwrap_or(1) {
+                    if let Some(stop) = debugger.step() {
+                        writeln!(output, "{stop}")?;
+                        break;
+                    }
+                }
+                show_registers(debugger, &mut output)?;
+            }
+            (Some("c"), _) => {
+                let stop = debugger.run();
+                show_output(debugger, &mut output)?;
+                writeln!(output, "{stop}")?;
+                show_registers(debugger, &mut output)?;
+            }
+            (Some("b"), Some(arg)) => {
+                let set = if let Ok(addr) = arg.parse() {
+                    toggle(&mut debugger.breakpoints, addr)
+                } else if let Some(op) = Op::from_mnemonic(arg) {
+                    toggle(&mut debugger.op_breakpoints, op)
+                } else {
+                    writeln!(output, "no such address or operation: {arg}")?;
+                    continue;
+                };
+                writeln!(output, "breakpoint {}", if set { "set" } else { "cleared" })?;
+            }
+            (Some("w"), Some(arg)) => match arg.parse() {
+                Ok(addr) => {
+                    let set = toggle(&mut debugger.watchpoints, addr);
+                    writeln!(output, "watchpoint {}", if set { "set" } else { "cleared" })?;
+                }
+                Err(err) => writeln!(output, "{err}")?,
+            },
+            (Some("i"), Some(_)) => {
+                let rest = line.trim_start()[1..].trim();
+                if let Some(text) = rest.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
+                    debugger.input.extend(text.bytes().map(i64::from));
+                    debugger.input.push_back(i64::from(b'\n'));
+                } else {
+                    match rest
+                        .split_whitespace()
+                        .map(str::parse::<i64>)
+                        .collect::<Result<Vec<_>, _>>()
+                    {
+                        Ok(values) => debugger.input.extend(values),
+                        Err(err) => writeln!(output, "{err}")?,
+                    }
+                }
+            }
+            (Some("m"), Some(addr)) => match addr.parse::<usize>() {
+                Ok(addr) => {
+                    let n = words.next().and_then(|n| n.parse().ok()).unwrap_or(8);
+                    let values: Vec<String> = (addr..addr.saturating_add(n.min(256)))
+                        .map(|i| debugger.vm.mem[i].to_string())
+                        .collect();
+                    writeln!(output, "[{addr}] {}", values.join(", "))?;
+                }
+                Err(err) => writeln!(output, "{err}")?,
+            },
+            (Some("r"), _) => show_registers(debugger, &mut output)?,
+            (Some("t"), _) => {
+                for entry in &debugger.trace {
+                    writeln!(output, "{entry}")?;
+                }
+                debugger.tracing = !debugger.tracing;
+                let state = if debugger.tracing { "on" } else { "off" };
+                writeln!(output, "tracing {state}")?;
+            }
+            (Some("q"), _) => return Ok(()),
+            (None, _) => {}
+            _ => write!(output, "{HELP}")?,
+        }
+        show_output(debugger, &mut output)?;
+        write!(output, "> ")?;
+        output.flush()?;
+    }
+    Ok(())
+}
+
+fn toggle<T: Eq + Hash>(set: &mut HashSet<T>, value: T) -> bool {
+    if set.remove(&value) {
+        false
+    } else {
+        set.insert(value);
+        true
+    }
+}
+
+fn show_registers(debugger: &Debugger, output: &mut impl Write) -> io::Result<()> {
+    let instruction = debugger
+        .instruction()
+        .map_or("(not an instruction)".to_string(), |i| i.to_string());
+    writeln!(
+        output,
+        "ip={} r={} {instruction}",
+        debugger.vm.ip(),
+        debugger.vm.base()
+    )
+}
+
+fn show_output(debugger: &mut Debugger, output: &mut impl Write) -> io::Result<()> {
+    for value in debugger.output.drain(..) {
+        match u8::try_from(value) {
+            Ok(c) if c.is_ascii_graphic() || c.is_ascii_whitespace() => {
+                write!(output, "{}", char::from(c))?;
+            }
+            _ => writeln!(output, "{value}")?,
+        }
+    }
+    Ok(())
+}
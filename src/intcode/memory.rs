@@ -0,0 +1,180 @@
+use std::{
+    collections::HashMap,
+    ops::{Index, IndexMut},
+};
+
+const PAGE_BITS: u32 = 8;
+const PAGE_SIZE: usize = 1 << PAGE_BITS;
+
+/// An instruction word split into its opcode and parameter mode digits.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(super) struct Decoded {
+    pub op: u8,
+    pub modes: [u8; 3],
+}
+
+/// The parameter modes of every instruction word with valid modes, indexed by the word divided by
+/// 100, and worked out up front. Looking them up is much cheaper than dividing the word into
+/// digits, and unlike caching decoded instructions per address it needs nothing invalidating when
+/// a program modifies itself, or copying when a VM is cloned.
+static MODES: [[u8; 3]; 223] = Decoded::modes();
+
+impl Decoded {
+    #[expect(clippy::cast_possible_truncation)]
+    const fn modes() -> [[u8; 3]; 223] {
+        let mut res = [[0; 3]; 223];
+        let mut i = 0;
+        while i < res.len() {
+            res[i] = [(i % 10) as u8, (i / 10 % 10) as u8, (i / 100) as u8];
+            i += 1;
+        }
+        res
+    }
+
+    /// The instruction `word`, or `None` if its opcode isn't valid. Words too big for the table
+    /// have an invalid mode, which is only reported when the parameter is used.
+    #[inline]
+    fn new(word: i64) -> Option<Self> {
+        let op = u8::try_from(word % 100).ok()?;
+        if !matches!(op, 1..=9 | 99) {
+            return None;
+        }
+        match usize::try_from(word / 100).ok().and_then(|i| MODES.get(i)) {
+            Some(&modes) => Some(Self { op, modes }),
+            None => Some(Self::new_slow(op, word)),
+        }
+    }
+
+    #[cold]
+    fn new_slow(op: u8, word: i64) -> Self {
+        let digit = |n: u32| u8::try_from(word / 10_i64.pow(n + 1) % 10).unwrap();
+        Self {
+            op,
+            modes: [digit(1), digit(2), digit(3)],
+        }
+    }
+}
+
+/// Intcode memory, which is unbounded but mostly unused. The program and anything just past it
+/// live in one flat vec, so that reading and writing them is as cheap as can be, and the rarely
+/// touched high addresses in a map of pages allocated on first write.
+#[derive(Clone, Debug)]
+pub struct Memory {
+    low: Vec<i64>,
+    /// Addresses below this are put in `low` when written, rather than in `high`.
+    low_limit: usize,
+    high: HashMap<usize, Box<[i64; PAGE_SIZE]>>,
+}
+
+impl Memory {
+    pub fn new(initial: &[i64]) -> Self {
+        Self {
+            low: initial.to_vec(),
+            low_limit: (2 * initial.len()).next_power_of_two().max(PAGE_SIZE),
+            high: HashMap::new(),
+        }
+    }
+
+    /// The instruction at `addr`, or `None` if its opcode isn't valid.
+    #[inline]
+    pub(super) fn decode(&self, addr: usize) -> Option<Decoded> {
+        Decoded::new(self[addr])
+    }
+
+    /// Runs of memory, as start addresses and values in order of address, which together cover
+    /// everything that isn't zero. The first starts at zero, and covers the program and whatever's
+    /// near it.
+    pub fn segments(&self) -> Vec<(usize, Vec<i64>)> {
+        let mut low = self.low.clone();
+        let len = low
+            .iter()
+            .rposition(|&value| value != 0)
+            .map_or(0, |i| i + 1);
+        low.truncate(len);
+        let mut high: Vec<(usize, Vec<i64>)> = self
+            .high
+            .iter()
+            .filter(|(_, page)| page.iter().any(|&value| value != 0))
+            .map(|(&i, page)| (i << PAGE_BITS, page.to_vec()))
+            .collect();
+        high.sort_unstable_by_key(|&(start, _)| start);
+        [(0, low)].into_iter().chain(high).collect()
+    }
+
+    /// Memory which is zero except for the given runs of values. A run starting at zero is treated
+    /// like the program given to `new`.
+    pub fn from_segments(segments: impl IntoIterator<Item = (usize, Vec<i64>)>) -> Self {
+        let mut segments: Vec<_> = segments.into_iter().collect();
+        let initial = match segments.iter().position(|&(start, _)| start == 0) {
+            Some(i) => segments.swap_remove(i).1,
+            None => Vec::new(),
+        };
+        let mut mem = Self::new(&initial);
+        for (start, values) in segments {
+            for (addr, value) in (start..).zip(values) {
+                mem[addr] = value;
+            }
+        }
+        mem
+    }
+
+    #[cold]
+    fn high(&self, addr: usize) -> &i64 {
+        self.high
+            .get(&(addr >> PAGE_BITS))
+            .map_or(&0, |page| &page[addr % PAGE_SIZE])
+    }
+
+    #[cold]
+    fn high_mut(&mut self, addr: usize) -> &mut i64 {
+        if addr < self.low_limit {
+            self.low.resize(addr + 1, 0);
+            return &mut self.low[addr];
+        }
+        let page = self
+            .high
+            .entry(addr >> PAGE_BITS)
+            .or_insert_with(|| Box::new([0; PAGE_SIZE]));
+        &mut page[addr % PAGE_SIZE]
+    }
+}
+
+impl PartialEq for Memory {
+    /// Memories are equal if every address holds the same value, however it's stored.
+    fn eq(&self, other: &Self) -> bool {
+        let low_len = self.low.len().max(other.low.len());
+        let high = self
+            .high
+            .keys()
+            .chain(other.high.keys())
+            .flat_map(|&page| page << PAGE_BITS..(page + 1) << PAGE_BITS);
+        (0..low_len)
+            .chain(high)
+            .all(|addr| self[addr] == other[addr])
+    }
+}
+
+impl Eq for Memory {}
+
+impl Index<usize> for Memory {
+    type Output = i64;
+
+    #[inline]
+    fn index(&self, addr: usize) -> &Self::Output {
+        match self.low.get(addr) {
+            Some(value) => value,
+            None => self.high(addr),
+        }
+    }
+}
+
+impl IndexMut<usize> for Memory {
+    #[inline]
+    fn index_mut(&mut self, addr: usize) -> &mut Self::Output {
+        if addr < self.low.len() {
+            &mut self.low[addr]
+        } else {
+            self.high_mut(addr)
+        }
+    }
+}
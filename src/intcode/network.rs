@@ -0,0 +1,164 @@
+use std::collections::{HashMap, VecDeque};
+
+use super::{State, VM};
+
+/// How the VMs in a network talk to each other, with messages of type `M`.
+pub struct Protocol<M> {
+    /// How many output values make up one message.
+    pub message_len: usize,
+    /// Where a message output by the given VM is going, and what it says.
+    pub decode: fn(usize, &[i64]) -> (usize, M),
+    /// The input values which deliver a message to a VM.
+    pub encode: fn(&M) -> Vec<i64>,
+    /// What a VM is given when it asks for input and has no messages waiting, or `None` to leave it
+    /// waiting.
+    pub idle_input: Option<i64>,
+}
+
+/// Something happening to a custom node.
+pub enum Event<M> {
+    /// A message arrived.
+    Message(M),
+    /// A whole round passed without any VM receiving or sending a message.
+    Idle,
+}
+
+struct Machine<M> {
+    vm: VM,
+    inbox: VecDeque<M>,
+    input: VecDeque<i64>,
+    output: Vec<i64>,
+    halted: bool,
+}
+
+type CustomNode<'a, M> = Box<dyn FnMut(Event<M>, &mut dyn FnMut(usize, M)) + 'a>;
+
+/// VMs exchanging messages, with addresses `0..n` for the `n` VMs given to `new`. Other addresses
+/// can be handled by custom nodes written in Rust, which are handed each message sent to them, and
+/// told when the network goes idle. Either can send messages, to any address, so the topology is
+/// whatever the nodes make it.
+///
+/// VMs are run round-robin, each until it halts or waits for input with no messages left, and
+/// messages are delivered as soon as they're complete, so they arrive in the order they were sent.
+pub struct Network<'a, M> {
+    machines: Vec<Machine<M>>,
+    custom: HashMap<usize, CustomNode<'a, M>>,
+    protocol: Protocol<M>,
+}
+
+impl<'a, M> Network<'a, M> {
+    pub fn new(vms: Vec<VM>, protocol: Protocol<M>) -> Self {
+        Self {
+            machines: vms
+                .into_iter()
+                .map(|vm| Machine {
+                    vm,
+                    inbox: VecDeque::new(),
+                    input: VecDeque::new(),
+                    output: Vec::new(),
+                    halted: false,
+                })
+                .collect(),
+            custom: HashMap::new(),
+            protocol,
+        }
+    }
+
+    /// Handle messages to `addr` with a closure, which is given each `Event` and a function for
+    /// sending messages of its own.
+    pub fn add_node<F>(&mut self, addr: usize, node: F)
+    where
+        F: FnMut(Event<M>, &mut dyn FnMut(usize, M)) + 'a,
+    {
+        assert!(addr >= self.machines.len(), "address {addr} is a VM");
+        self.custom.insert(addr, Box::new(node));
+    }
+
+    /// The VM at `addr`, for setting it up before the network runs.
+    pub fn vm_mut(&mut self, addr: usize) -> &mut VM {
+        &mut self.machines[addr].vm
+    }
+
+    /// Messages waiting for the VM at `addr`, which will stay there if it's halted.
+    pub fn inbox(&self, addr: usize) -> &VecDeque<M> {
+        &self.machines[addr].inbox
+    }
+
+    /// Send a message from outside the network.
+    pub fn send(&mut self, addr: usize, message: M) {
+        let mut pending = VecDeque::from([(addr, message)]);
+        while let Some((addr, message)) = pending.pop_front() {
+            if let Some(machine) = self.machines.get_mut(addr) {
+                machine.inbox.push_back(message);
+            } else {
+                let node = self
+                    .custom
+                    .get_mut(&addr)
+                    .unwrap_or_else(|| panic!("nothing at address {addr}"));
+                node(Event::Message(message), &mut |addr, message| {
+                    pending.push_back((addr, message));
+                });
+            }
+        }
+    }
+
+    /// Run each VM in turn until it halts or is waiting on an empty inbox, delivering the messages
+    /// it sends. Returns whether anything happened: a VM receiving or sending a message.
+    pub fn round(&mut self) -> bool {
+        let mut active = false;
+        for addr in 0..self.machines.len() {
+            let machine = &mut self.machines[addr];
+            if machine.halted {
+                continue;
+            }
+            if machine.inbox.is_empty() {
+                machine.input.extend(self.protocol.idle_input);
+            } else {
+                active = true;
+                for message in machine.inbox.drain(..) {
+                    machine.input.extend((self.protocol.encode)(&message));
+                }
+            }
+            let state = machine
+                .vm
+                .run_until_blocked(&mut machine.input, &mut machine.output);
+            machine.halted = state == State::Halt;
+
+            let len = self.protocol.message_len;
+            let complete = machine.output.len() / len * len;
+            let output: Vec<i64> = machine.output.drain(..complete).collect();
+            for message in output.chunks(len) {
+                active = true;
+                let (to, message) = (self.protocol.decode)(addr, message);
+                self.send(to, message);
+            }
+        }
+        active
+    }
+
+    /// Tell every custom node that the network is idle. Returns whether any of them sent anything.
+    fn idle(&mut self) -> bool {
+        let mut sent = Vec::new();
+        for node in self.custom.values_mut() {
+            node(Event::Idle, &mut |addr, message| sent.push((addr, message)));
+        }
+        let active = !sent.is_empty();
+        for (addr, message) in sent {
+            self.send(addr, message);
+        }
+        active
+    }
+
+    /// Run rounds until `stop` returns true, or the network goes quiet for good: idle, with no
+    /// custom node sending anything when told so. Returns whether it was stopped.
+    pub fn run_until(&mut self, mut stop: impl FnMut() -> bool) -> bool {
+        loop {
+            if stop() {
+                return true;
+            }
+            if !self.round() && !self.idle() {
+                return false;
+            }
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use std::{fs, io, path::Path};
+
+use serde_json::{Value, json};
+
+use super::{VM, memory::Memory};
+
+const MAGIC: &[u8; 4] = b"ICVM";
+
+/// Everything about a VM at some moment, for restoring it later, or saving it to a file and
+/// loading it again. Taking one is as cheap as cloning the VM, since memory is copied on write.
+#[derive(Clone)]
+pub struct Snapshot {
+    vm: VM,
+}
+
+impl Snapshot {
+    pub(super) fn new(vm: VM) -> Self {
+        Self { vm }
+    }
+
+    pub(super) fn vm(&self) -> &VM {
+        &self.vm
+    }
+
+    /// The snapshot as a JSON object, with memory as `[start, [values...]]` runs covering
+    /// everything that isn't zero.
+    pub fn to_json(&self) -> String {
+        json!({
+            "ip": self.vm.ip,
+            "base": self.vm.base,
+            "budget": self.vm.budget,
+            "memory": self.vm.mem.segments(),
+        })
+        .to_string()
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let value: Value = serde_json::from_str(json).map_err(|err| err.to_string())?;
+        let field = |name: &str| value.get(name).ok_or(format!("missing {name}"));
+        let int = |value: &Value| value.as_i64().ok_or(format!("not an integer: {value}"));
+        let addr = |value: &Value| {
+            value
+                .as_u64()
+                .and_then(|n| usize::try_from(n).ok())
+                .ok_or(format!("not an address: {value}"))
+        };
+
+        let budget = field("budget")?;
+        let budget = if budget.is_null() {
+            None
+        } else {
+            Some(budget.as_u64().ok_or(format!("not a budget: {budget}"))?)
+        };
+        let segments = field("memory")?
+            .as_array()
+            .ok_or("memory isn't an array")?
+            .iter()
+            .map(|segment| match segment.as_array().map(Vec::as_slice) {
+                Some([start, Value::Array(values)]) => Ok((
+                    addr(start)?,
+                    values.iter().map(int).collect::<Result<_, _>>()?,
+                )),
+                _ => Err(format!("not a run of memory: {segment}")),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::new(VM {
+            mem: Memory::from_segments(segments),
+            ip: addr(field("ip")?)?,
+            base: int(field("base")?)?,
+            budget,
+            coverage: None,
+        }))
+    }
+
+    /// The snapshot in a compact binary form: a magic number, then little endian 64 bit integers
+    /// for the instruction pointer, relative base, budget (-1 for none), and number of runs of
+    /// memory, then for each run its start, length and values.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let segments = self.vm.mem.segments();
+        let budget = self
+            .vm
+            .budget
+            .map_or(-1, |budget| i64::try_from(budget).unwrap());
+        let mut words = vec![
+            i64::try_from(self.vm.ip).unwrap(),
+            self.vm.base,
+            budget,
+            i64::try_from(segments.len()).unwrap(),
+        ];
+        for (start, values) in segments {
+            words.push(i64::try_from(start).unwrap());
+            words.push(i64::try_from(values.len()).unwrap());
+            words.extend(values);
+        }
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend(words.iter().flat_map(|word| word.to_le_bytes()));
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let words = bytes.strip_prefix(MAGIC).ok_or("not a snapshot")?;
+        if words.len() % 8 != 0 {
+            return Err("truncated snapshot".to_string());
+        }
+        let mut words = words
+            .chunks(8)
+            .map(|word| i64::from_le_bytes(word.try_into().unwrap()));
+        let mut next = || words.next().ok_or("truncated snapshot");
+        let addr = |word: i64| usize::try_from(word).map_err(|_| format!("not an address: {word}"));
+
+        let ip = addr(next()?)?;
+        let base = next()?;
+        let budget = u64::try_from(next()?).ok();
+        let mut segments = Vec::new();
+        for _ in 0..addr(next()?)? {
+            let start = addr(next()?)?;
+            let len = addr(next()?)?;
+            segments.push((start, (0..len).map(|_| next()).collect::<Result<_, _>>()?));
+        }
+        if next().is_ok() {
+            return Err("trailing data after snapshot".to_string());
+        }
+        Ok(Self::new(VM {
+            mem: Memory::from_segments(segments),
+            ip,
+            base,
+            budget,
+            coverage: None,
+        }))
+    }
+
+    /// Write the snapshot to a file, as JSON if its name ends in `.json`, otherwise in binary.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        if is_json(path.as_ref()) {
+            fs::write(path, self.to_json())
+        } else {
+            fs::write(path, self.to_bytes())
+        }
+    }
+
+    /// Read a snapshot written by `save`.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let res = if is_json(path.as_ref()) {
+            Self::from_json(&fs::read_to_string(path)?)
+        } else {
+            Self::from_bytes(&fs::read(path)?)
+        };
+        res.map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+}
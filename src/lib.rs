@@ -9,19 +9,23 @@
 use regex::Regex;
 use std::{fs, path::Path};
 
+pub mod geometry;
+pub mod graph;
+pub mod grid;
 pub mod intcode;
+pub mod memo;
+pub mod render;
+pub mod search;
 pub mod solutions;
 
 mod combinatorics;
+mod cycle;
 mod freqs;
-mod grid;
 mod hash;
 mod lex_ord;
 mod number_theory;
 mod ocr;
 mod part;
-mod search;
-mod unbounded_vec;
 mod uniq;
 mod vm_2018;
 
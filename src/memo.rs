@@ -0,0 +1,161 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    hash::{BuildHasher, Hash},
+    marker::PhantomData,
+};
+
+/// Somewhere to keep the results of a memoised function.
+pub trait Cache<K, V> {
+    fn get(&mut self, key: &K) -> Option<&V>;
+    fn insert(&mut self, key: K, value: V);
+}
+
+impl<K: Eq + Hash, V, S: BuildHasher> Cache<K, V> for HashMap<K, V, S> {
+    fn get(&mut self, key: &K) -> Option<&V> {
+        HashMap::get(self, key)
+    }
+
+    fn insert(&mut self, key: K, value: V) {
+        HashMap::insert(self, key, value);
+    }
+}
+
+/// A cache for small dense `usize` keys (a length or an index, say), which avoids hashing
+/// altogether.
+pub struct VecCache<V>(Vec<Option<V>>);
+
+impl<V> VecCache<V> {
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+}
+
+impl<V> Default for VecCache<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> Cache<usize, V> for VecCache<V> {
+    fn get(&mut self, &key: &usize) -> Option<&V> {
+        self.0.get(key)?.as_ref()
+    }
+
+    fn insert(&mut self, key: usize, value: V) {
+        if key >= self.0.len() {
+            self.0.resize_with(key + 1, || None);
+        }
+        self.0[key] = Some(value);
+    }
+}
+
+/// A cache which holds at most `capacity` results, discarding the least recently used when it's
+/// full.
+pub struct Lru<K, V> {
+    capacity: usize,
+    tick: u64,
+    entries: HashMap<K, (V, u64)>,
+    // The key last used at each tick.
+    order: BTreeMap<u64, K>,
+}
+
+impl<K, V> Lru<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0);
+        Self {
+            capacity,
+            tick: 0,
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+        }
+    }
+}
+
+impl<K: Clone + Eq + Hash, V> Cache<K, V> for Lru<K, V> {
+    fn get(&mut self, key: &K) -> Option<&V> {
+        let (value, last_used) = self.entries.get_mut(key)?;
+        self.order.remove(last_used);
+        self.tick += 1;
+        *last_used = self.tick;
+        self.order.insert(self.tick, key.clone());
+        Some(value)
+    }
+
+    fn insert(&mut self, key: K, value: V) {
+        if let Some((_, last_used)) = self.entries.get(&key) {
+            self.order.remove(last_used);
+        } else if self.entries.len() >= self.capacity {
+            let (_, oldest) = self.order.pop_first().unwrap();
+            self.entries.remove(&oldest);
+        }
+        self.tick += 1;
+        self.order.insert(self.tick, key.clone());
+        self.entries.insert(key, (value, self.tick));
+    }
+}
+
+/// Memoises a recursive function. The function takes a `&mut Memo` in place of a cache, and wraps
+/// its body in a call to `get` with a key identifying its arguments. The body is a closure which is
+/// handed the `Memo` back, so that it can recurse as normal. Counts cache hits and misses, for
+/// checking that the key is doing its job.
+pub struct Memo<K, V, C = HashMap<K, V>> {
+    cache: C,
+    hits: usize,
+    misses: usize,
+    marker: PhantomData<fn(K) -> V>,
+}
+
+impl<K: Eq + Hash, V> Memo<K, V> {
+    pub fn new() -> Self {
+        Self::with_cache(HashMap::new())
+    }
+}
+
+impl<K: Eq + Hash, V> Default for Memo<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V, C: Cache<K, V>> Memo<K, V, C> {
+    pub fn with_cache(cache: C) -> Self {
+        Self {
+            cache,
+            hits: 0,
+            misses: 0,
+            marker: PhantomData,
+        }
+    }
+
+    /// The cached result for `key` if there is one, otherwise the result of `f` (which is then
+    /// cached).
+    pub fn get<F>(&mut self, key: K, f: F) -> V
+    where
+        V: Clone,
+        F: FnOnce(&mut Self) -> V,
+    {
+        if let Some(res) = self.cache.get(&key) {
+            self.hits += 1;
+            return res.clone();
+        }
+        self.misses += 1;
+        let res = f(self);
+        self.cache.insert(key, res.clone());
+        res
+    }
+
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+}
+
+impl<K, V, C> fmt::Display for Memo<K, V, C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} hits, {} misses", self.hits, self.misses)
+    }
+}
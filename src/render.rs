@@ -0,0 +1,367 @@
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    io::{self, Write},
+    time::Duration,
+};
+
+use crate::grid::{Grid, IntoChar, Vector};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+pub const BLACK: Rgb = Rgb(0, 0, 0);
+pub const WHITE: Rgb = Rgb(255, 255, 255);
+pub const GREY: Rgb = Rgb(128, 128, 128);
+pub const RED: Rgb = Rgb(220, 50, 47);
+pub const GREEN: Rgb = Rgb(133, 153, 0);
+pub const YELLOW: Rgb = Rgb(181, 137, 0);
+pub const BLUE: Rgb = Rgb(38, 139, 210);
+pub const MAGENTA: Rgb = Rgb(211, 54, 130);
+pub const CYAN: Rgb = Rgb(42, 161, 152);
+
+/// A character with optional foreground and background colours. A `Grid<Cell>` is a frame which can
+/// be drawn to a terminal, recorded, or exported as an image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cell {
+    pub ch: char,
+    pub fg: Option<Rgb>,
+    pub bg: Option<Rgb>,
+}
+
+impl Cell {
+    pub const fn new(ch: char) -> Self {
+        Self {
+            ch,
+            fg: None,
+            bg: None,
+        }
+    }
+
+    #[must_use]
+    pub const fn fg(self, fg: Rgb) -> Self {
+        Self {
+            fg: Some(fg),
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub const fn bg(self, bg: Rgb) -> Self {
+        Self {
+            bg: Some(bg),
+            ..self
+        }
+    }
+
+    /// The colour of the cell when drawn as a single pixel: the background if there is one, then
+    /// the foreground, and failing that white for visible characters and black for whitespace.
+    pub fn colour(self) -> Rgb {
+        self.bg.or(self.fg).unwrap_or(if self.ch.is_whitespace() {
+            BLACK
+        } else {
+            WHITE
+        })
+    }
+
+    fn write_ansi(self, s: &mut String) {
+        s.push_str("\u{1b}[0");
+        if let Some(Rgb(r, g, b)) = self.fg {
+            write!(s, ";38;2;{r};{g};{b}").unwrap();
+        }
+        if let Some(Rgb(r, g, b)) = self.bg {
+            write!(s, ";48;2;{r};{g};{b}").unwrap();
+        }
+        s.push('m');
+        s.push(self.ch);
+    }
+}
+
+impl Rgb {
+    /// The colour a fraction `t` of the way from `self` to `other`.
+    #[must_use]
+    pub fn lerp(self, other: Self, t: f64) -> Self {
+        let channel = |a: u8, b: u8| {
+            let c = f64::from(a) + (f64::from(b) - f64::from(a)) * t.clamp(0.0, 1.0);
+            #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let c = c.round() as u8;
+            c
+        };
+        Self(
+            channel(self.0, other.0),
+            channel(self.1, other.1),
+            channel(self.2, other.2),
+        )
+    }
+}
+
+impl IntoChar for &Cell {
+    fn into_char(self) -> char {
+        self.ch
+    }
+}
+
+impl<T> From<&Grid<T>> for Grid<Cell>
+where
+    for<'a> &'a T: IntoChar,
+{
+    fn from(grid: &Grid<T>) -> Self {
+        grid.map(|_, value| Cell::new(value.into_char()))
+    }
+}
+
+/// Foreground colours for the characters a grid is drawn with, so that each kind of value in a
+/// simulation stands out. Characters without a colour are drawn plain.
+#[derive(Debug, Clone, Default)]
+pub struct Palette {
+    colours: HashMap<char, Rgb>,
+}
+
+impl Palette {
+    pub fn new<I: IntoIterator<Item = (char, Rgb)>>(colours: I) -> Self {
+        Self {
+            colours: colours.into_iter().collect(),
+        }
+    }
+
+    pub fn cell(&self, ch: char) -> Cell {
+        match self.colours.get(&ch) {
+            Some(&fg) => Cell::new(ch).fg(fg),
+            None => Cell::new(ch),
+        }
+    }
+
+    /// A frame of `grid` in this palette's colours.
+    pub fn paint<T>(&self, grid: &Grid<T>) -> Grid<Cell>
+    where
+        for<'a> &'a T: IntoChar,
+    {
+        grid.map(|_, value| self.cell(value.into_char()))
+    }
+}
+
+impl Grid<Cell> {
+    /// Set the background of every position in `points` (a path returned by a search, say).
+    /// Positions outside the frame are ignored.
+    pub fn highlight<I: IntoIterator<Item = Vector>>(&mut self, points: I, bg: Rgb) {
+        for point in points {
+            if let Some(cell) = self.get_mut(point) {
+                cell.bg = Some(bg);
+            }
+        }
+    }
+
+    /// Shade the background of each position in `order` (the order in which a search expanded
+    /// states, say) by when it first appears, from blue for the earliest to red for the latest.
+    /// Positions outside the frame are ignored.
+    pub fn trace<I: IntoIterator<Item = Vector>>(&mut self, order: I) {
+        let mut first = Grid::new(None, self.size);
+        let mut n: usize = 0;
+        for point in order {
+            if let Some(seen @ None) = first.get_mut(point) {
+                *seen = Some(n);
+                n += 1;
+            }
+        }
+        for (point, seen) in first.iter() {
+            if let Some(i) = seen {
+                #[expect(clippy::cast_precision_loss)]
+                let t = *i as f64 / (n - 1).max(1) as f64;
+                self[point].bg = Some(BLUE.lerp(RED, t));
+            }
+        }
+    }
+
+    /// The frame as text with ANSI true colour escape codes. Codes are only emitted when the colour
+    /// changes, and every line ends with a reset.
+    pub fn to_ansi(&self) -> String {
+        let mut res = String::new();
+        for y in 0..self.size.y {
+            let mut prev = None;
+            for x in 0..self.size.x {
+                let cell = self[[x, y]];
+                if prev == Some((cell.fg, cell.bg)) {
+                    res.push(cell.ch);
+                } else {
+                    cell.write_ansi(&mut res);
+                    prev = Some((cell.fg, cell.bg));
+                }
+            }
+            res.push_str("\u{1b}[0m\n");
+        }
+        res
+    }
+
+    fn pixels(&self, scale: usize) -> impl Iterator<Item = Vec<Rgb>> + '_ {
+        (0..self.size.y).flat_map(move |y| {
+            let row: Vec<Rgb> = (0..self.size.x)
+                .flat_map(|x| [self[[x, y]].colour()].repeat(scale))
+                .collect();
+            vec![row; scale]
+        })
+    }
+
+    /// Write the frame as a PNG image with each cell drawn as a `scale` by `scale` square. The
+    /// image data is stored uncompressed, which keeps things simple at the expense of file size.
+    pub fn write_png<W: Write>(&self, mut w: W, scale: usize) -> io::Result<()> {
+        let width = u32::try_from(self.size.x).unwrap() * u32::try_from(scale).unwrap();
+        let height = u32::try_from(self.size.y).unwrap() * u32::try_from(scale).unwrap();
+
+        let mut raw = Vec::new();
+        for row in self.pixels(scale) {
+            // Each scanline starts with filter type 0 (none).
+            raw.push(0);
+            for Rgb(r, g, b) in row {
+                raw.extend([r, g, b]);
+            }
+        }
+
+        let mut ihdr = Vec::new();
+        ihdr.extend(width.to_be_bytes());
+        ihdr.extend(height.to_be_bytes());
+        // Bit depth 8, colour type 2 (RGB), default compression, filter, and interlace methods.
+        ihdr.extend([8, 2, 0, 0, 0]);
+
+        w.write_all(b"\x89PNG\r\n\x1a\n")?;
+        write_png_chunk(&mut w, *b"IHDR", &ihdr)?;
+        write_png_chunk(&mut w, *b"IDAT", &zlib_stored(&raw))?;
+        write_png_chunk(&mut w, *b"IEND", &[])
+    }
+}
+
+fn write_png_chunk<W: Write>(w: &mut W, kind: [u8; 4], data: &[u8]) -> io::Result<()> {
+    w.write_all(&u32::try_from(data.len()).unwrap().to_be_bytes())?;
+    w.write_all(&kind)?;
+    w.write_all(data)?;
+    w.write_all(&crc32(kind.iter().chain(data)).to_be_bytes())
+}
+
+fn crc32<'a>(bytes: impl Iterator<Item = &'a u8>) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                0xedb8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// Wrap `data` in a zlib stream made of uncompressed deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut res = vec![0x78, 0x01];
+    let mut chunks = data.chunks(0xffff).peekable();
+    if chunks.peek().is_none() {
+        res.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(chunk) = chunks.next() {
+        let len = u16::try_from(chunk.len()).unwrap();
+        res.push(u8::from(chunks.peek().is_none()));
+        res.extend(len.to_le_bytes());
+        res.extend((!len).to_le_bytes());
+        res.extend(chunk);
+    }
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + u32::from(byte)) % 65521;
+        b = (b + a) % 65521;
+    }
+    res.extend(((b << 16) | a).to_be_bytes());
+    res
+}
+
+/// Draws frames and individual cells in place on a terminal using ANSI escape codes. The cursor is
+/// hidden until the `Terminal` is dropped.
+pub struct Terminal<W: Write> {
+    out: W,
+}
+
+impl<W: Write> Terminal<W> {
+    pub fn new(mut out: W) -> io::Result<Self> {
+        out.write_all(b"\x1b[?25l\x1b[2J")?;
+        Ok(Self { out })
+    }
+
+    /// Draw `frame` with its top left corner at the top left of the terminal.
+    pub fn draw(&mut self, frame: &Grid<Cell>) -> io::Result<()> {
+        self.out.write_all(b"\x1b[H")?;
+        self.out.write_all(frame.to_ansi().as_bytes())?;
+        self.out.flush()
+    }
+
+    /// Draw a single cell at `pos`, where (0, 0) is the top left of the terminal.
+    pub fn draw_cell(&mut self, pos: Vector, cell: Cell) -> io::Result<()> {
+        let mut s = format!("\x1b[{};{}H", pos.y + 1, pos.x + 1);
+        cell.write_ansi(&mut s);
+        s.push_str("\x1b[0m");
+        self.out.write_all(s.as_bytes())?;
+        self.out.flush()
+    }
+
+    /// Draw plain text starting at `pos`.
+    pub fn draw_str(&mut self, pos: Vector, s: &str) -> io::Result<()> {
+        write!(self.out, "\x1b[{};{}H{s}", pos.y + 1, pos.x + 1)?;
+        self.out.flush()
+    }
+}
+
+impl<W: Write> Drop for Terminal<W> {
+    fn drop(&mut self) {
+        // Park the cursor below anything we might have drawn. Errors are ignored since there's
+        // nothing useful to do with them here.
+        let _ = self.out.write_all(b"\x1b[?25h\x1b[999;1H\n");
+        let _ = self.out.flush();
+    }
+}
+
+/// Records a sequence of frames as an animation, for simulations where watching the state evolve
+/// is more useful than the final answer.
+#[derive(Default)]
+pub struct Recorder {
+    size: Vector,
+    elapsed: Duration,
+    events: Vec<(Duration, String)>,
+}
+
+impl Recorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record `frame`, to be shown `delay` after the previous one.
+    pub fn record(&mut self, frame: &Grid<Cell>, delay: Duration) {
+        self.size = self.size.sup(&frame.size);
+        self.elapsed += delay;
+        // Terminals in raw mode need an explicit carriage return.
+        let data = frame.to_ansi().replace('\n', "\r\n");
+        self.events
+            .push((self.elapsed, format!("\x1b[H\x1b[2J{data}")));
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Write the recording in [asciicast v2](https://docs.asciinema.org/manual/asciicast/v2/)
+    /// format, which can be played back with `asciinema play`.
+    pub fn write_asciicast<W: Write>(&self, mut w: W) -> io::Result<()> {
+        let header = serde_json::json!({
+            "version": 2,
+            "width": self.size.x,
+            "height": self.size.y + 1,
+        });
+        writeln!(w, "{header}")?;
+        for (time, data) in &self.events {
+            writeln!(w, "{}", serde_json::json!([time.as_secs_f64(), "o", data]))?;
+        }
+        Ok(())
+    }
+}
@@ -1,12 +1,18 @@
 use std::{
+    cell::{Cell, RefCell},
     cmp::{Ordering, Reverse},
-    collections::{BinaryHeap, HashSet, VecDeque},
-    hash::Hash,
-    iter,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque, hash_map::Entry},
+    fmt,
+    hash::{BuildHasher, Hash, RandomState},
+    iter, mem,
+    num::NonZeroUsize,
     ops::Add,
+    sync::{Mutex, MutexGuard},
+    thread,
+    time::{Duration, Instant},
 };
 
-use num::PrimInt;
+use num::{PrimInt, Zero};
 
 trait Queue {
     type Item;
@@ -28,6 +34,19 @@ impl<T> Queue for VecDeque<T> {
     }
 }
 
+/// Last-in-first-out, for searching depth first.
+impl<T> Queue for Vec<T> {
+    type Item = T;
+
+    fn push(&mut self, value: Self::Item) {
+        Vec::push(self, value);
+    }
+
+    fn pop(&mut self) -> Option<Self::Item> {
+        Vec::pop(self)
+    }
+}
+
 struct CostValue<V, O> {
     cost: O,
     value: V,
@@ -106,6 +125,152 @@ pub fn no_filter<S>(_: &S) -> bool {
     true
 }
 
+/// Counts what a search gets up to, for comparing heuristics and filters objectively. Wrap the
+/// `adjacent` and `filter` arguments of a search with `adjacent` and `filter` (or `weighted` for
+/// `dijkstra_weighted`, which has no filter), run the search, and then inspect or print the
+/// `Stats`. The order in which states are expanded is just the order the search yields them, so
+/// there's nothing extra needed to trace that.
+#[derive(Default)]
+pub struct Stats {
+    pushed: Cell<usize>,
+    popped: Cell<usize>,
+    pruned: Cell<usize>,
+    expanded: Cell<usize>,
+    max_frontier: Cell<usize>,
+    started: Cell<Option<Instant>>,
+    // The time at which each thousandth state was expanded.
+    laps: RefCell<Vec<Instant>>,
+}
+
+impl Stats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count the states pushed by `adjacent`, and the states it expands.
+    pub fn adjacent<'a, S, A>(&'a self, mut adjacent: A) -> impl FnMut(&S, &mut dyn FnMut(S)) + 'a
+    where
+        A: FnMut(&S, &mut dyn FnMut(S)) + 'a,
+    {
+        move |state, push| {
+            self.expand();
+            adjacent(state, &mut |a| {
+                self.push();
+                push(a);
+            });
+        }
+    }
+
+    /// Like `adjacent` but for the weighted searches.
+    pub fn weighted<'a, S, O, A>(
+        &'a self,
+        mut adjacent: A,
+    ) -> impl FnMut(&S, &mut dyn FnMut(S, O)) + 'a
+    where
+        A: FnMut(&S, &mut dyn FnMut(S, O)) + 'a,
+    {
+        move |state, push| {
+            self.expand();
+            adjacent(state, &mut |a, cost| {
+                self.push();
+                push(a, cost);
+            });
+        }
+    }
+
+    /// Count the states popped from the frontier, and how many of those `filter` prunes.
+    pub fn filter<'a, S, F>(&'a self, mut filter: F) -> impl FnMut(&S) -> bool + 'a
+    where
+        F: FnMut(&S) -> bool + 'a,
+    {
+        move |state| {
+            self.start();
+            self.popped.set(self.popped.get() + 1);
+            let res = filter(state);
+            if !res {
+                self.pruned.set(self.pruned.get() + 1);
+            }
+            res
+        }
+    }
+
+    fn start(&self) {
+        if self.started.get().is_none() {
+            self.started.set(Some(Instant::now()));
+        }
+    }
+
+    fn expand(&self) {
+        self.start();
+        self.expanded.set(self.expanded.get() + 1);
+        if self.expanded.get().is_multiple_of(1000) {
+            self.laps.borrow_mut().push(Instant::now());
+        }
+    }
+
+    fn push(&self) {
+        self.pushed.set(self.pushed.get() + 1);
+        self.max_frontier
+            .set(self.max_frontier.get().max(self.frontier()));
+    }
+
+    pub fn pushed(&self) -> usize {
+        self.pushed.get()
+    }
+
+    /// The number of states popped from the frontier. When there's no wrapped filter to see pops,
+    /// this is the number of states expanded.
+    pub fn popped(&self) -> usize {
+        self.popped.get().max(self.expanded.get())
+    }
+
+    pub fn pruned(&self) -> usize {
+        self.pruned.get()
+    }
+
+    pub fn expanded(&self) -> usize {
+        self.expanded.get()
+    }
+
+    /// The number of states pushed (including the start) which haven't been popped yet. For
+    /// `dijkstra_weighted` this is an overestimate, since the states it discards without queueing
+    /// (or later finds stale) are invisible to us, and so count as never popped. If only `filter`
+    /// is wrapped then pushes aren't seen either, and this is zero.
+    pub fn frontier(&self) -> usize {
+        (1 + self.pushed()).saturating_sub(self.popped())
+    }
+
+    pub fn max_frontier(&self) -> usize {
+        self.max_frontier.get()
+    }
+
+    /// The mean time taken to expand each thousand states, or `None` if there haven't been a
+    /// thousand yet.
+    pub fn per_thousand(&self) -> Option<Duration> {
+        let laps = self.laps.borrow();
+        let last = laps.last()?;
+        Some((*last - self.started.get()?) / u32::try_from(laps.len()).unwrap())
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "expanded {}, pushed {}, popped {}, pruned {}, max frontier {}",
+            self.expanded(),
+            self.pushed(),
+            self.popped(),
+            self.pruned(),
+            self.max_frontier(),
+        )?;
+        if let Some(per_thousand) = self.per_thousand() {
+            write!(f, ", {per_thousand:?} per 1k expanded")?;
+        }
+        Ok(())
+    }
+}
+
 fn search<Q, S, A, F>(
     mut queue: Q,
     start: S,
@@ -178,17 +343,717 @@ where
     })
 }
 
+/// Search a state space depth first. Only the unexplored siblings of states on the current branch
+/// are held in memory, so this is the one to reach for when the space is too big to search breadth
+/// first, and `filter` can prune branches which can't lead anywhere useful.
+pub fn depth_first<S, A, F>(start: S, adjacent: A, filter: F) -> impl Iterator<Item = S>
+where
+    A: FnMut(&S, &mut dyn FnMut(S)),
+    F: FnMut(&S) -> bool,
+{
+    search(Vec::new(), start, adjacent, filter)
+}
+
+/// Search a state space one level at a time, keeping only the `width` lowest cost states that pass
+/// `filter` at each level. Memory is bounded by `width`, but there's no guarantee of finding the
+/// best (or any) solution, so results want checking.
+pub fn beam<S, A, F, C, O>(
+    start: S,
+    mut adjacent: A,
+    mut filter: F,
+    mut cost: C,
+    width: usize,
+) -> impl Iterator<Item = S>
+where
+    A: FnMut(&S, &mut dyn FnMut(S)),
+    F: FnMut(&S) -> bool,
+    C: FnMut(&S) -> O,
+    O: Ord,
+{
+    let mut level = if filter(&start) {
+        vec![start]
+    } else {
+        Vec::new()
+    };
+    let mut next = Vec::new();
+    iter::from_fn(move || {
+        if level.is_empty() {
+            next.sort_by_cached_key(|state| cost(state));
+            level = next
+                .drain(..)
+                .filter(|state| filter(state))
+                .take(width)
+                .collect();
+            // Pop from the back, lowest cost first.
+            level.reverse();
+        }
+        let state = level.pop()?;
+        adjacent(&state, &mut |a| next.push(a));
+        Some(state)
+    })
+}
+
+/// The number of steps in a shortest path from `start` to `goal`, found by searching breadth first
+/// from both ends at once and stopping when the two searches meet. `forward` pushes the states
+/// reachable from a state in one step, and `backward` the states from which it can be reached in
+/// one step (often these are the same). When the branching factor is high this visits far fewer
+/// states than searching from `start` alone.
+pub fn bidirectional<S, F, B>(start: S, goal: S, mut forward: F, mut backward: B) -> Option<usize>
+where
+    S: Clone + Eq + Hash,
+    F: FnMut(&S, &mut dyn FnMut(S)),
+    B: FnMut(&S, &mut dyn FnMut(S)),
+{
+    if start == goal {
+        return Some(0);
+    }
+    let mut distances = [
+        HashMap::from([(start.clone(), 0)]),
+        HashMap::from([(goal.clone(), 0)]),
+    ];
+    let mut frontiers = [vec![start], vec![goal]];
+    while !frontiers[0].is_empty() && !frontiers[1].is_empty() {
+        // Expand a whole level of the smaller frontier.
+        let side = usize::from(frontiers[1].len() < frontiers[0].len());
+        let mut best = None;
+        let mut next = Vec::new();
+        for state in std::mem::take(&mut frontiers[side]) {
+            let distance = distances[side][&state] + 1;
+            let mut push = |a: S| {
+                if distances[side].contains_key(&a) {
+                    return;
+                }
+                if let Some(&d) = distances[1 - side].get(&a) {
+                    best = Some(best.map_or(distance + d, |b: usize| b.min(distance + d)));
+                }
+                distances[side].insert(a.clone(), distance);
+                next.push(a);
+            };
+            if side == 0 {
+                forward(&state, &mut push);
+            } else {
+                backward(&state, &mut push);
+            }
+        }
+        // Any meeting point found while expanding this level is as good as it gets, but a better
+        // one might still turn up later in the same level, so we only stop at the end.
+        if best.is_some() {
+            return best;
+        }
+        frontiers[side] = next;
+    }
+    None
+}
+
+/// Find a state satisfying `goal` by
+/// [iterative deepening A*](https://en.wikipedia.org/wiki/Iterative_deepening_A*): repeated depth
+/// first searches, each pruning states whose cost plus heuristic exceeds a threshold, which grows
+/// to the smallest pruned value each time round. Memory use is proportional to the depth of the
+/// search rather than the number of states, at the expense of revisiting states. Since nothing is
+/// remembered between states, costs must strictly increase along every edge for this to
+/// terminate. If the heuristic never overestimates, the goal found has the lowest cost.
+pub fn ida_star<S, A, C, D, G, O>(
+    start: S,
+    mut adjacent: A,
+    mut cost: C,
+    mut heuristic: D,
+    mut goal: G,
+) -> Option<S>
+where
+    S: Clone,
+    A: FnMut(&S, &mut dyn FnMut(S)),
+    C: FnMut(&S) -> O,
+    D: FnMut(&S) -> O,
+    G: FnMut(&S) -> bool,
+    O: Copy + Ord + Add<Output = O>,
+{
+    let mut threshold = cost(&start) + heuristic(&start);
+    loop {
+        let mut next_threshold = None;
+        let res = depth_first(start.clone(), &mut adjacent, |state| {
+            let f = cost(state) + heuristic(state);
+            if f > threshold {
+                next_threshold = Some(next_threshold.map_or(f, |t: O| t.min(f)));
+                false
+            } else {
+                true
+            }
+        })
+        .find(&mut goal);
+        if res.is_some() {
+            return res;
+        }
+        threshold = next_threshold?;
+    }
+}
+
+type Frontier<S, K, O> = BinaryHeap<Reverse<CostValue<(S, K, O), O>>>;
+type BoxedAdjacent<'a, S> = Box<dyn FnMut(&S, &mut dyn FnMut(S)) + 'a>;
+type BoxedCost<'a, S, O> = Box<dyn FnMut(&S, &O) -> O + 'a>;
+
+/// Search a graph with weighted edges, nearest first. `adjacent` pushes each neighbour along with
+/// the cost of the edge to it, so unlike `dijkstra` the state doesn't need to carry its own cost.
+/// Each reachable state is yielded exactly once, along with its distance from the start.
+pub fn dijkstra_weighted<S, A, O>(start: S, mut adjacent: A) -> impl Iterator<Item = (S, O)>
+where
+    S: Clone + Eq + Hash,
+    A: FnMut(&S, &mut dyn FnMut(S, O)),
+    O: Copy + Ord + Zero,
+{
+    let mut distances = HashMap::from([(start.clone(), O::zero())]);
+    let mut heap = BinaryHeap::from([Reverse(CostValue {
+        cost: O::zero(),
+        value: start,
+    })]);
+    iter::from_fn(move || {
+        while let Some(Reverse(CostValue { cost, value: state })) = heap.pop() {
+            // We only push a state when we find a strictly shorter route to it, so any entry which
+            // doesn't match the best known distance is stale.
+            if distances[&state] < cost {
+                continue;
+            }
+            adjacent(&state, &mut |a, edge_cost| {
+                let distance = cost + edge_cost;
+                if distances.get(&a).is_none_or(|&d| distance < d) {
+                    distances.insert(a.clone(), distance);
+                    heap.push(Reverse(CostValue {
+                        cost: distance,
+                        value: a,
+                    }));
+                }
+            });
+            return Some((state, cost));
+        }
+        None
+    })
+}
+
+/// The distance to every state reachable from `start`. See `dijkstra_weighted`.
+pub fn distances<S, A, O>(start: S, adjacent: A) -> HashMap<S, O>
+where
+    S: Clone + Eq + Hash,
+    A: FnMut(&S, &mut dyn FnMut(S, O)),
+    O: Copy + Ord + Zero,
+{
+    dijkstra_weighted(start, adjacent).collect()
+}
+
+/// A map from keys to the order in which they were first reached, split into shards behind their
+/// own locks so that worker threads can claim keys as they find them without queueing on one lock.
+struct Claims<K, O> {
+    hasher: RandomState,
+    shards: Vec<Mutex<HashMap<K, O>>>,
+}
+
+impl<K: Eq + Hash, O: Ord> Claims<K, O> {
+    const SHARDS: usize = 64;
+
+    fn new() -> Self {
+        Self {
+            hasher: RandomState::new(),
+            shards: iter::repeat_with(Mutex::default)
+                .take(Self::SHARDS)
+                .collect(),
+        }
+    }
+
+    fn shard(&self, key: &K) -> MutexGuard<'_, HashMap<K, O>> {
+        let i = usize::try_from(self.hasher.hash_one(key) % Self::SHARDS as u64).unwrap();
+        self.shards[i].lock().unwrap()
+    }
+
+    /// Claim `key`, unless it's already been claimed by something earlier than `order`. A claim
+    /// can be taken away by something earlier later on, so check it's still held with `holds`.
+    fn claim(&self, key: K, order: O) -> bool {
+        match self.shard(&key).entry(key) {
+            Entry::Vacant(entry) => {
+                entry.insert(order);
+                true
+            }
+            Entry::Occupied(mut entry) => {
+                let earlier = order < *entry.get();
+                if earlier {
+                    entry.insert(order);
+                }
+                earlier
+            }
+        }
+    }
+
+    fn holds(&self, key: &K, order: &O) -> bool {
+        self.shard(key).get(key) == Some(order)
+    }
+
+    fn contains(&self, key: &K) -> bool {
+        self.shard(key).contains_key(key)
+    }
+}
+
+/// Calls `expand` on each of `states` across `threads` threads, returning the results in the
+/// same order as the states. `expand` is also given the index of the state.
+fn expand_parallel<S, T, E>(threads: usize, states: &[S], expand: &E) -> Vec<T>
+where
+    S: Sync,
+    T: Send,
+    E: Fn(usize, &S) -> T + Sync,
+{
+    if threads <= 1 || states.len() < 2 {
+        return states
+            .iter()
+            .enumerate()
+            .map(|(i, state)| expand(i, state))
+            .collect();
+    }
+    let chunk_len = states.len().div_ceil(threads);
+    thread::scope(|scope| {
+        let handles: Vec<_> = states
+            .chunks(chunk_len)
+            .enumerate()
+            .map(|(n, chunk)| {
+                scope.spawn(move || {
+                    let start = n * chunk_len;
+                    (start..)
+                        .zip(chunk)
+                        .map(|(i, state)| expand(i, state))
+                        .collect::<Vec<T>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect()
+    })
+}
+
+/// The number of threads to give the parallel searches: one per available core.
+pub fn threads() -> usize {
+    thread::available_parallelism().map_or(1, NonZeroUsize::get)
+}
+
+/// As `breadth_first` with a `hash_filter`, but expands each level of the search across `threads`
+/// threads. They're spawned afresh for every level, so this is only worth it when `adjacent` is
+/// expensive and levels are wide, with thousands of states to share out. The workers share a
+/// sharded visited set, and claim each child's key as it's pushed, dropping children whose key has
+/// been claimed before. Each claim is tagged with where the child would come in the sequential
+/// search, and the earliest wins, so the states yielded are exactly those `breadth_first` would
+/// yield, in the same order. Each level is expanded as a whole before any of it is yielded.
+pub fn parallel_breadth_first<S, A, H, K>(
+    threads: usize,
+    start: S,
+    adjacent: A,
+    hash_key: H,
+) -> impl Iterator<Item = S>
+where
+    S: Send + Sync,
+    A: Fn(&S, &mut dyn FnMut(S)) + Sync,
+    H: Fn(&S) -> K + Sync,
+    K: Eq + Hash + Send,
+{
+    // Claims are ordered by depth, then the index of the parent in its level, then the order in
+    // which the parent pushed the child.
+    let claims = Claims::new();
+    claims.claim(hash_key(&start), (0, 0, 0));
+    let mut depth = 0;
+    let mut next = vec![start];
+    let mut level = Vec::new().into_iter();
+    iter::from_fn(move || {
+        if level.len() == 0 {
+            let current = mem::take(&mut next);
+            depth += 1;
+            next = expand_parallel(threads, &current, &|i, state| {
+                let mut children = Vec::new();
+                let mut j = 0;
+                adjacent(state, &mut |child| {
+                    let order = (depth, i, j);
+                    j += 1;
+                    if claims.claim(hash_key(&child), order) {
+                        children.push((order, child));
+                    }
+                });
+                children
+            })
+            .into_iter()
+            .flatten()
+            .filter(|(order, child)| claims.holds(&hash_key(child), order))
+            .map(|(_, child)| child)
+            .collect();
+            level = current.into_iter();
+        }
+        level.next()
+    })
+}
+
+/// As `dijkstra` with a `hash_filter`, but expands every state at the current lowest cost at once,
+/// across `threads` threads. The workers share a sharded visited set, claiming each state's key before
+/// expanding it and dropping children whose key has already been claimed. States are yielded in
+/// cost order, but states of equal cost may come out in a different order to `dijkstra`.
+pub fn parallel_dijkstra<S, A, H, K, C, O>(
+    threads: usize,
+    start: S,
+    adjacent: A,
+    hash_key: H,
+    cost: C,
+) -> impl Iterator<Item = S>
+where
+    S: Send + Sync,
+    A: Fn(&S, &mut dyn FnMut(S)) + Sync,
+    H: Fn(&S) -> K + Sync,
+    K: Eq + Hash + Send,
+    C: Fn(&S) -> O,
+    O: Ord,
+{
+    let visited = Claims::new();
+    let mut heap = BinaryHeap::from([Reverse(CostValue {
+        cost: cost(&start),
+        value: start,
+    })]);
+    let mut batch = Vec::new().into_iter();
+    iter::from_fn(move || {
+        while batch.len() == 0 {
+            let Reverse(first) = heap.pop()?;
+            let mut current = vec![first.value];
+            while heap
+                .peek()
+                .is_some_and(|Reverse(next)| next.cost == first.cost)
+            {
+                current.push(heap.pop().unwrap().0.value);
+            }
+            let children = expand_parallel(threads, &current, &|_, state| {
+                if !visited.claim(hash_key(state), ()) {
+                    return None;
+                }
+                let mut children = Vec::new();
+                adjacent(state, &mut |child| {
+                    if !visited.contains(&hash_key(&child)) {
+                        children.push(child);
+                    }
+                });
+                Some(children)
+            });
+            let mut expanded = Vec::new();
+            for (state, children) in current.into_iter().zip(children) {
+                let Some(children) = children else {
+                    continue;
+                };
+                expanded.push(state);
+                heap.extend(children.into_iter().map(|value| {
+                    Reverse(CostValue {
+                        cost: cost(&value),
+                        value,
+                    })
+                }));
+            }
+            batch = expanded.into_iter();
+        }
+        batch.next()
+    })
+}
+
+struct Visit<K, O> {
+    cost: O,
+    parents: Vec<K>,
+    expanded: bool,
+}
+
+/// A min-cost-first search which remembers how it reached each state, so that paths can be
+/// reconstructed after the fact. States are identified by `hash_key`, which plays the same role as
+/// in `hash_filter`: each key is only expanded once, at its lowest cost. Every predecessor giving
+/// that lowest cost is recorded, not just the first, so that we can recover all the best paths.
+///
+/// Iterating yields states as they are expanded, in cost order. Once a state has been yielded its
+/// paths are complete (assuming costs strictly increase along each edge).
+pub struct Paths<'a, S, K, O> {
+    heap: Frontier<S, K, O>,
+    visits: HashMap<K, Visit<K, O>>,
+    adjacent: BoxedAdjacent<'a, S>,
+    hash_key: Box<dyn FnMut(&S) -> K + 'a>,
+    // The cost of a state given the cost of its parent.
+    cost: BoxedCost<'a, S, O>,
+    // The priority of a state given its cost.
+    priority: BoxedCost<'a, S, O>,
+}
+
+impl<'a, S, K, O> Paths<'a, S, K, O>
+where
+    K: Clone + Eq + Hash,
+    O: Clone + Ord,
+{
+    fn new<A, H, C, P>(
+        start: S,
+        start_cost: O,
+        adjacent: A,
+        mut hash_key: H,
+        cost: C,
+        priority: P,
+    ) -> Self
+    where
+        A: FnMut(&S, &mut dyn FnMut(S)) + 'a,
+        H: FnMut(&S) -> K + 'a,
+        C: FnMut(&S, &O) -> O + 'a,
+        P: FnMut(&S, &O) -> O + 'a,
+    {
+        let key = hash_key(&start);
+        let mut res = Self {
+            heap: BinaryHeap::new(),
+            visits: HashMap::from([(
+                key.clone(),
+                Visit {
+                    cost: start_cost.clone(),
+                    parents: Vec::new(),
+                    expanded: false,
+                },
+            )]),
+            adjacent: Box::new(adjacent),
+            hash_key: Box::new(hash_key),
+            cost: Box::new(cost),
+            priority: Box::new(priority),
+        };
+        res.push(start, key, start_cost);
+        res
+    }
+
+    fn push(&mut self, state: S, key: K, cost: O) {
+        self.heap.push(Reverse(CostValue {
+            cost: (self.priority)(&state, &cost),
+            value: (state, key, cost),
+        }));
+    }
+
+    fn relax(&mut self, state: S, parent: &K, parent_cost: &O) {
+        let key = (self.hash_key)(&state);
+        let cost = (self.cost)(&state, parent_cost);
+        match self.visits.entry(key.clone()) {
+            Entry::Vacant(entry) => {
+                entry.insert(Visit {
+                    cost: cost.clone(),
+                    parents: vec![parent.clone()],
+                    expanded: false,
+                });
+            }
+            Entry::Occupied(mut entry) => {
+                let visit = entry.get_mut();
+                match cost.cmp(&visit.cost) {
+                    Ordering::Less => {
+                        *visit = Visit {
+                            cost: cost.clone(),
+                            parents: vec![parent.clone()],
+                            expanded: false,
+                        };
+                    }
+                    Ordering::Equal => {
+                        if !visit.parents.contains(parent) {
+                            visit.parents.push(parent.clone());
+                        }
+                        return;
+                    }
+                    Ordering::Greater => return,
+                }
+            }
+        }
+        self.push(state, key, cost);
+    }
+
+    /// The lowest cost found so far to reach `key`.
+    pub fn cost(&self, key: &K) -> Option<&O> {
+        self.visits.get(key).map(|visit| &visit.cost)
+    }
+
+    /// Every predecessor of `key` on a lowest cost path.
+    pub fn predecessors(&self, key: &K) -> &[K] {
+        self.visits.get(key).map_or(&[], |visit| &visit.parents)
+    }
+
+    /// A lowest cost path from the start to `key`, inclusive at both ends.
+    pub fn path(&self, key: &K) -> Vec<K> {
+        let mut res = vec![key.clone()];
+        while let Some(parent) = self.predecessors(res.last().unwrap()).first() {
+            res.push(parent.clone());
+        }
+        res.reverse();
+        res
+    }
+
+    /// The keys which appear on any lowest cost path from the start to `key`.
+    pub fn on_best_paths(&self, key: &K) -> HashSet<K> {
+        let mut res = HashSet::from([key.clone()]);
+        let mut stack = vec![key];
+        while let Some(key) = stack.pop() {
+            for parent in self.predecessors(key) {
+                if res.insert(parent.clone()) {
+                    stack.push(parent);
+                }
+            }
+        }
+        res
+    }
+}
+
+impl<S, K, O> Iterator for Paths<'_, S, K, O>
+where
+    K: Clone + Eq + Hash,
+    O: Clone + Ord,
+{
+    type Item = S;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(Reverse(CostValue {
+            value: (state, key, cost),
+            ..
+        })) = self.heap.pop()
+        {
+            let visit = self.visits.get_mut(&key).unwrap();
+            if visit.expanded || cost > visit.cost {
+                continue;
+            }
+            visit.expanded = true;
+            let mut adjacent = Vec::new();
+            (self.adjacent)(&state, &mut |a| adjacent.push(a));
+            for a in adjacent {
+                self.relax(a, &key, &cost);
+            }
+            return Some(state);
+        }
+        None
+    }
+}
+
+/// Like `breadth_first` with a `hash_filter`, but recording paths.
+pub fn breadth_first_paths<'a, S, A, H, K>(
+    start: S,
+    adjacent: A,
+    hash_key: H,
+) -> Paths<'a, S, K, usize>
+where
+    A: FnMut(&S, &mut dyn FnMut(S)) + 'a,
+    H: FnMut(&S) -> K + 'a,
+    K: Clone + Eq + Hash,
+{
+    Paths::new(
+        start,
+        0,
+        adjacent,
+        hash_key,
+        |_, &depth| depth + 1,
+        |_, &depth| depth,
+    )
+}
+
+/// Like `dijkstra` with a `hash_filter`, but recording paths.
+pub fn dijkstra_paths<'a, S, A, H, K, C, O>(
+    start: S,
+    adjacent: A,
+    hash_key: H,
+    mut cost: C,
+) -> Paths<'a, S, K, O>
+where
+    A: FnMut(&S, &mut dyn FnMut(S)) + 'a,
+    H: FnMut(&S) -> K + 'a,
+    K: Clone + Eq + Hash,
+    C: FnMut(&S) -> O + 'a,
+    O: Clone + Ord,
+{
+    let start_cost = cost(&start);
+    Paths::new(
+        start,
+        start_cost,
+        adjacent,
+        hash_key,
+        move |state, _| cost(state),
+        |_, cost| cost.clone(),
+    )
+}
+
+/// Like `a_star` with a `hash_filter`, but recording paths.
+pub fn a_star_paths<'a, S, A, H, K, C, D, O>(
+    start: S,
+    adjacent: A,
+    hash_key: H,
+    mut cost: C,
+    mut heuristic: D,
+) -> Paths<'a, S, K, O>
+where
+    A: FnMut(&S, &mut dyn FnMut(S)) + 'a,
+    H: FnMut(&S) -> K + 'a,
+    K: Clone + Eq + Hash,
+    C: FnMut(&S) -> O + 'a,
+    D: FnMut(&S) -> O + 'a,
+    O: Clone + Ord + Add<Output = O>,
+{
+    let start_cost = cost(&start);
+    Paths::new(
+        start,
+        start_cost,
+        adjacent,
+        hash_key,
+        move |state, _| cost(state),
+        move |state, cost| cost.clone() + heuristic(state),
+    )
+}
+
+/// Why a search over a monotone predicate couldn't get started.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MonotoneError<N> {
+    /// The predicate was already true at the low bound.
+    TrueAtLow(N),
+    /// The predicate was still false at the high bound.
+    FalseAtHigh(N),
+    /// The predicate was already false at the low bound, when it should have started out true.
+    FalseAtLow(N),
+    /// The predicate was still true at the high bound, when it should have ended up false.
+    TrueAtHigh(N),
+    /// No bound was found before the search ran out of numbers.
+    Unbounded,
+}
+
+impl<N: fmt::Display> fmt::Display for MonotoneError<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::TrueAtLow(n) => write!(f, "predicate already true at low bound {n}"),
+            Self::FalseAtHigh(n) => write!(f, "predicate still false at high bound {n}"),
+            Self::FalseAtLow(n) => write!(f, "predicate already false at low bound {n}"),
+            Self::TrueAtHigh(n) => write!(f, "predicate still true at high bound {n}"),
+            Self::Unbounded => write!(f, "no bound found before overflowing"),
+        }
+    }
+}
+
+impl<N: fmt::Debug + fmt::Display> std::error::Error for MonotoneError<N> {}
+
 /// Finds the smallest value where pred is true, assuming that it is false for all lower values, and
 /// true for all higher, given a low and high initial bound.
-pub fn binary<N, F>(mut low: N, mut high: N, mut pred: F) -> N
+pub fn binary<N, F>(low: N, high: N, pred: F) -> N
+where
+    N: PrimInt + fmt::Debug,
+    F: FnMut(N) -> bool,
+{
+    try_binary(low, high, pred).unwrap()
+}
+
+/// As `binary`, but reports a predicate which isn't false at `low` and true at `high`.
+pub fn try_binary<N, F>(low: N, high: N, mut pred: F) -> Result<N, MonotoneError<N>>
+where
+    N: PrimInt,
+    F: FnMut(N) -> bool,
+{
+    if pred(low) {
+        return Err(MonotoneError::TrueAtLow(low));
+    }
+    if !pred(high) {
+        return Err(MonotoneError::FalseAtHigh(high));
+    }
+    Ok(binary_between(low, high, pred))
+}
+
+// The binary search proper, for bounds already known to have pred false at `low` and true at
+// `high`, which aren't evaluated again.
+fn binary_between<N, F>(mut low: N, mut high: N, mut pred: F) -> N
 where
     N: PrimInt,
     F: FnMut(N) -> bool,
 {
-    assert!(!pred(low));
-    assert!(pred(high));
     while high - low > N::one() {
-        let mid = (high + low) / N::from(2).unwrap();
+        let mid = low + (high - low) / N::from(2).unwrap();
         if pred(mid) {
             high = mid;
         } else {
@@ -198,19 +1063,193 @@ where
     high
 }
 
+/// Finds the largest value where pred is true, assuming that it is true for all lower values, and
+/// false for all higher, given a low and high initial bound.
+pub fn binary_last<N, F>(low: N, high: N, pred: F) -> N
+where
+    N: PrimInt + fmt::Debug,
+    F: FnMut(N) -> bool,
+{
+    try_binary_last(low, high, pred).unwrap()
+}
+
+/// As `binary_last`, but reports a predicate which isn't true at `low` and false at `high`.
+pub fn try_binary_last<N, F>(low: N, high: N, mut pred: F) -> Result<N, MonotoneError<N>>
+where
+    N: PrimInt,
+    F: FnMut(N) -> bool,
+{
+    if !pred(low) {
+        return Err(MonotoneError::FalseAtLow(low));
+    }
+    if pred(high) {
+        return Err(MonotoneError::TrueAtHigh(high));
+    }
+    Ok(binary_between(low, high, |n| !pred(n)) - N::one())
+}
+
 /// Finds the smallest value where pred is true, assuming that it is false for all lower values, and
 /// true for all higher, given only a low initial bound.
-pub fn exponential<N, F>(mut low: N, mut pred: F) -> N
+pub fn exponential<N, F>(low: N, pred: F) -> N
+where
+    N: PrimInt + fmt::Debug,
+    F: FnMut(N) -> bool,
+{
+    try_exponential(low, pred).unwrap()
+}
+
+/// As `exponential`, but reports a predicate which is true at `low`, or which never becomes true.
+pub fn try_exponential<N, F>(low: N, mut pred: F) -> Result<N, MonotoneError<N>>
+where
+    N: PrimInt,
+    F: FnMut(N) -> bool,
+{
+    if pred(low) {
+        return Err(MonotoneError::TrueAtLow(low));
+    }
+    gallop_up(low, pred)
+}
+
+// Doubles the step up from `low`, already known to have pred false, until pred is true.
+fn gallop_up<N, F>(mut low: N, mut pred: F) -> Result<N, MonotoneError<N>>
 where
     N: PrimInt,
     F: FnMut(N) -> bool,
 {
-    assert!(!pred(low));
     let mut size = N::one();
-    while !pred(low + size) {
-        low = low + size;
-        size = size * N::from(2).unwrap();
+    loop {
+        let high = low.checked_add(&size).ok_or(MonotoneError::Unbounded)?;
+        if pred(high) {
+            return Ok(binary_between(low, high, pred));
+        }
+        low = high;
+        size = size.checked_mul(&N::from(2).unwrap()).unwrap_or(size);
     }
+}
 
-    binary(low, low + size, pred)
+/// Finds the smallest value where pred is true, assuming that it is false for all lower values, and
+/// true for all higher, given only a high initial bound.
+pub fn exponential_down<N, F>(high: N, pred: F) -> N
+where
+    N: PrimInt + fmt::Debug,
+    F: FnMut(N) -> bool,
+{
+    try_exponential_down(high, pred).unwrap()
+}
+
+/// As `exponential_down`, but reports a predicate which is false at `high`, or which never becomes
+/// false.
+pub fn try_exponential_down<N, F>(high: N, mut pred: F) -> Result<N, MonotoneError<N>>
+where
+    N: PrimInt,
+    F: FnMut(N) -> bool,
+{
+    if !pred(high) {
+        return Err(MonotoneError::FalseAtHigh(high));
+    }
+    gallop_down(high, pred)
+}
+
+// Doubles the step down from `high`, already known to have pred true, until pred is false.
+fn gallop_down<N, F>(mut high: N, mut pred: F) -> Result<N, MonotoneError<N>>
+where
+    N: PrimInt,
+    F: FnMut(N) -> bool,
+{
+    let mut size = N::one();
+    loop {
+        let low = high.checked_sub(&size).ok_or(MonotoneError::Unbounded)?;
+        if !pred(low) {
+            return Ok(binary_between(low, high, pred));
+        }
+        high = low;
+        size = size.checked_mul(&N::from(2).unwrap()).unwrap_or(size);
+    }
+}
+
+/// Finds the smallest value where pred is true, assuming that it is false for all lower values, and
+/// true for all higher, starting from a guess which may be on either side.
+pub fn exponential_both<N, F>(guess: N, pred: F) -> N
+where
+    N: PrimInt + fmt::Debug,
+    F: FnMut(N) -> bool,
+{
+    try_exponential_both(guess, pred).unwrap()
+}
+
+/// As `exponential_both`, but reports a predicate which is never true or never false.
+pub fn try_exponential_both<N, F>(guess: N, mut pred: F) -> Result<N, MonotoneError<N>>
+where
+    N: PrimInt,
+    F: FnMut(N) -> bool,
+{
+    if pred(guess) {
+        gallop_down(guess, pred)
+    } else {
+        gallop_up(guess, pred)
+    }
+}
+
+/// Finds where pred becomes true to within `tolerance`, assuming that it is false for all lower
+/// values, and true for all higher, given a low and high initial bound. The result is a value where
+/// pred is true.
+pub fn bisect<F>(low: f64, high: f64, tolerance: f64, pred: F) -> f64
+where
+    F: FnMut(f64) -> bool,
+{
+    try_bisect(low, high, tolerance, pred).unwrap()
+}
+
+/// As `bisect`, but reports a predicate which isn't false at `low` and true at `high`.
+pub fn try_bisect<F>(
+    mut low: f64,
+    mut high: f64,
+    tolerance: f64,
+    mut pred: F,
+) -> Result<f64, MonotoneError<f64>>
+where
+    F: FnMut(f64) -> bool,
+{
+    assert!(tolerance > 0.0);
+    if pred(low) {
+        return Err(MonotoneError::TrueAtLow(low));
+    }
+    if !pred(high) {
+        return Err(MonotoneError::FalseAtHigh(high));
+    }
+    while high - low > tolerance {
+        let mid = low + (high - low) / 2.0;
+        // Below the tolerance there may be no float strictly between the bounds.
+        if mid <= low || mid >= high {
+            break;
+        }
+        if pred(mid) {
+            high = mid;
+        } else {
+            low = mid;
+        }
+    }
+    Ok(high)
+}
+
+/// Finds the value in `low..=high` minimising f, assuming that f is unimodal there: strictly
+/// decreasing up to the minimum and non-decreasing after (as any convex function is). Returns the
+/// first minimum if there's a run of them. This is usually called ternary search, but on integers
+/// comparing neighbouring values lets each step halve the range rather than cut a third off it.
+pub fn ternary<N, F, O>(mut low: N, mut high: N, mut f: F) -> N
+where
+    N: PrimInt,
+    F: FnMut(N) -> O,
+    O: Ord,
+{
+    assert!(low <= high);
+    while low < high {
+        let mid = low + (high - low) / N::from(2).unwrap();
+        if f(mid) <= f(mid + N::one()) {
+            high = mid;
+        } else {
+            low = mid + N::one();
+        }
+    }
+    low
 }
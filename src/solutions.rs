@@ -471,7 +471,7 @@ pub fn build() -> BTreeMap<u16, BTreeMap<u8, Solution>> {
         Solution {
             part1: Some(|input| year2015::day22::part1(input).to_string()),
             part2: Some(|input| year2015::day22::part2(input).to_string()),
-            tests: None,
+            tests: Some(year2015::day22::tests),
         },
     );
 
@@ -1335,7 +1335,7 @@ pub fn build() -> BTreeMap<u16, BTreeMap<u8, Solution>> {
         Solution {
             part1: Some(|input| year2019::day18::part1(input).to_string()),
             part2: Some(|input| year2019::day18::part2(input).to_string()),
-            tests: None,
+            tests: Some(year2019::day18::tests),
         },
     );
 
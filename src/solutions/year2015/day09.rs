@@ -1,47 +1,26 @@
-use std::collections::HashMap;
-
 use regex::Regex;
 
-use crate::{combinatorics::permute, uniq::Uniq};
+use crate::graph::Graph;
 
-fn parse(input: &str) -> HashMap<(&str, &str), u32> {
-    let mut distances = HashMap::new();
+fn parse(input: &str) -> Graph<&str, u32> {
+    let mut distances = Graph::undirected();
     for captures in Regex::new(r"(\w+) to (\w+) = (\d+)")
         .unwrap()
         .captures_iter(input)
     {
         let a = captures.get(1).unwrap().as_str();
         let b = captures.get(2).unwrap().as_str();
-        let dist = captures[3].parse().unwrap();
-        distances.insert((a, b), dist);
-        distances.insert((b, a), dist);
+        distances.add_edge(a, b, captures[3].parse().unwrap());
     }
     distances
 }
 
-fn part_(input: &str, best: fn(u32, u32) -> u32) -> u32 {
-    let distances = parse(input);
-    let distance = |route: &[&str]| {
-        route
-            .windows(2)
-            .map(|pair| distances[&(pair[0], pair[1])])
-            .sum()
-    };
-    let mut route: Vec<_> = distances.keys().map(|(a, _)| *a).uniq().collect();
-    route.sort_unstable();
-    let mut best_distance = distance(&route);
-    while permute(&mut route) {
-        best_distance = best(best_distance, distance(&route));
-    }
-    best_distance
-}
-
 pub fn part1(input: &str) -> u32 {
-    part_(input, std::cmp::min)
+    parse(input).shortest_route(None, false).unwrap()
 }
 
 pub fn part2(input: &str) -> u32 {
-    part_(input, std::cmp::max)
+    parse(input).longest_route(None, false).unwrap()
 }
 
 pub fn tests() {
@@ -135,58 +135,78 @@ impl State {
         state.turn = Turn::Player;
         state
     }
+
+    /// A lower bound on the mana still needed to win. Poison is the cheapest damage there is, at 173
+    /// mana for 18 hit points, and whatever's left of the current dose is free.
+    fn mana_needed(&self) -> i32 {
+        let poison = self
+            .effects
+            .get(&Effect::Poison)
+            .map_or(0, |turns| 3 * turns);
+        cmp::max(0, self.boss.hp - poison) * 173 / 18
+    }
+
+    fn won(&self) -> bool {
+        self.boss.hp <= 0
+    }
 }
 
-fn part_(hard_mode: bool, input: &str) -> i32 {
-    search::dijkstra(
-        State {
-            player: Character::PLAYER,
-            boss: Character::boss(input),
-            effects: BTreeMap::new(),
-            turn: Turn::Player,
-            hard_mode,
-            mana_spent: 0,
-        },
-        |state, push| {
-            let mut state = state.clone();
-            if state.hard_mode && state.turn == Turn::Player {
-                state.player.hp -= 1;
-                if state.player.hp <= 0 {
-                    return;
-                }
-            }
+fn start(hard_mode: bool, input: &str) -> State {
+    State {
+        player: Character::PLAYER,
+        boss: Character::boss(input),
+        effects: BTreeMap::new(),
+        turn: Turn::Player,
+        hard_mode,
+        mana_spent: 0,
+    }
+}
 
-            state.apply_effects();
-            if state.player.hp <= 0 {
-                return;
-            }
+fn adjacent(state: &State, push: &mut dyn FnMut(State)) {
+    let mut state = state.clone();
+    if state.hard_mode && state.turn == Turn::Player {
+        state.player.hp -= 1;
+        if state.player.hp <= 0 {
+            return;
+        }
+    }
 
-            if state.boss.hp <= 0 {
-                push(state);
-                return;
-            }
+    state.apply_effects();
+    if state.player.hp <= 0 {
+        return;
+    }
 
-            match state.turn {
-                Turn::Player => {
-                    for spell in &SPELLS {
-                        if spell.cost <= state.player.mana
-                            && spell
-                                .effect
-                                .is_none_or(|(effect, _)| !state.effects.contains_key(&effect))
-                        {
-                            push(state.player_attack(spell));
-                        }
-                    }
-                }
-                Turn::Boss => {
-                    push(state.boss_attack());
+    if state.won() {
+        push(state);
+        return;
+    }
+
+    match state.turn {
+        Turn::Player => {
+            for spell in &SPELLS {
+                if spell.cost <= state.player.mana
+                    && spell
+                        .effect
+                        .is_none_or(|(effect, _)| !state.effects.contains_key(&effect))
+                {
+                    push(state.player_attack(spell));
                 }
             }
-        },
+        }
+        Turn::Boss => {
+            push(state.boss_attack());
+        }
+    }
+}
+
+fn part_(hard_mode: bool, input: &str) -> i32 {
+    search::dijkstra(
+        start(hard_mode, input),
+        adjacent,
         search::id_filter(),
         |state| state.mana_spent,
     )
-    .find(|state| state.boss.hp <= 0)
+    .find(State::won)
     .unwrap()
     .mana_spent
 }
@@ -198,3 +218,36 @@ pub fn part1(input: &str) -> i32 {
 pub fn part2(input: &str) -> i32 {
     part_(true, input)
 }
+
+pub fn tests() {
+    // Iterative deepening finds the same fights as Dijkstra, though it's far too slow to use for the
+    // real thing: with no memory of where it's been, it retreads each fight many times over as the
+    // threshold creeps up. A beam a thousand fights wide finds them too, so long as it only keeps
+    // fights it hasn't seen, or the winners would keep pushing themselves forever.
+    for boss in ["Hit Points: 13\nDamage: 8", "Hit Points: 30\nDamage: 9"] {
+        for hard_mode in [false, true] {
+            let best = part_(hard_mode, boss);
+            let state = search::ida_star(
+                start(hard_mode, boss),
+                adjacent,
+                |state| state.mana_spent,
+                State::mana_needed,
+                State::won,
+            )
+            .unwrap();
+            assert_eq!(state.mana_spent, best);
+            let state = search::beam(
+                start(hard_mode, boss),
+                adjacent,
+                search::id_filter(),
+                |state| state.mana_spent,
+                1000,
+            )
+            .filter(State::won)
+            .min_by_key(|state| state.mana_spent)
+            .unwrap();
+            assert_eq!(state.mana_spent, best);
+        }
+    }
+    assert_eq!(part1("Hit Points: 13\nDamage: 8"), 212);
+}
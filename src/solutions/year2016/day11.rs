@@ -54,7 +54,6 @@ fn parse(input: &str) -> Vec<Item> {
 struct State {
     lift: u8,
     items: Vec<Item>,
-    steps: u8,
 }
 
 impl State {
@@ -95,32 +94,8 @@ impl State {
     }
 }
 
-// The best we can hope for is to move two items up and one down each step. Annoyingly, the
-// heuristic alone gives the correct answer for both parts (but fails on the example).
-fn heuristic(state: &State) -> u8 {
-    let mut res = 0;
-    let mut count = 0;
-    for floor in 0..=2 {
-        for item in &state.items {
-            if item.microchip == floor {
-                count += 1;
-            }
-            if item.generator == floor {
-                count += 1;
-            }
-        }
-        // At this point count covers all items on this floor, plus all items from lower floors that
-        // we've brought up with us. Add to res the number of steps to take them all to the next
-        // floor.
-        match count {
-            0 => {}
-            1 => res += 1,
-            _ => res += 2 * count - 3,
-        }
-    }
-    res
-}
-
+// Every move can be undone by moving the same items back, so adjacent serves as its own inverse
+// when searching backwards from the goal.
 fn adjacent(state: &State, push: &mut dyn FnMut(State)) {
     for lift in state.adjacent_floors() {
         let items_on_floor = state.items_on_floor();
@@ -141,42 +116,31 @@ fn adjacent(state: &State, push: &mut dyn FnMut(State)) {
 
             if state.is_safe() {
                 state.items.sort_unstable();
-                state.steps += 1;
                 push(state);
             }
         }
     }
 }
 
-fn part_(items: Vec<Item>) -> u8 {
-    search::a_star(
-        State {
-            lift: 0,
-            items,
-            steps: 0,
-        },
-        adjacent,
-        search::hash_filter(|state: &State| (state.lift, state.items.clone())),
-        |state| state.steps,
-        heuristic,
-    )
-    .find(|state| {
-        state.items.iter().all(
-            |Item {
-                 microchip,
-                 generator,
-             }| *microchip == 3 && *generator == 3,
-        )
-    })
-    .unwrap()
-    .steps
+fn part_(items: Vec<Item>) -> usize {
+    let goal = State {
+        lift: 3,
+        items: vec![
+            Item {
+                microchip: 3,
+                generator: 3,
+            };
+            items.len()
+        ],
+    };
+    search::bidirectional(State { lift: 0, items }, goal, adjacent, adjacent).unwrap()
 }
 
-pub fn part1(input: &str) -> u8 {
+pub fn part1(input: &str) -> usize {
     part_(parse(input))
 }
 
-pub fn part2(input: &str) -> u8 {
+pub fn part2(input: &str) -> usize {
     let mut items = parse(input);
     items.push(Item {
         microchip: 0,
@@ -1,83 +1,30 @@
-use std::{
-    cmp,
-    collections::{HashMap, HashSet},
-};
-
-use crate::{
-    combinatorics::permute,
-    grid::{Adjacent, Grid, Vector},
-    search,
-};
-
-fn distance(ducts: &HashSet<Vector>, a: Vector, b: Vector) -> u32 {
-    struct State {
-        pos: Vector,
-        steps: u32,
-    }
-    search::breadth_first(
-        State { pos: a, steps: 0 },
-        |state, push| {
-            for pos in state.pos.adjacent4() {
-                if ducts.contains(&pos) {
-                    push(State {
-                        pos,
-                        steps: state.steps + 1,
-                    });
-                }
-            }
-        },
-        search::hash_filter(|state: &State| state.pos),
-    )
-    .find(|state| state.pos == b)
-    .unwrap()
-    .steps
-}
-
-fn total_dist(dists: &HashMap<(char, char), u32>, route: &[char]) -> u32 {
-    route
-        .windows(2)
-        .map(|pair| dists[&(pair[0], pair[1])])
-        .sum()
-}
+use crate::{graph::Graph, grid::Grid, search};
 
 fn part_(return_to_start: bool, input: &str) -> u32 {
     let map = Grid::parse(input, |_, c| c);
 
-    let nodes: Vec<(Vector, char)> = map
-        .iter()
-        .filter(|(_, c)| c.is_ascii_digit())
-        .map(|(p, c)| (p, *c))
-        .collect();
-
-    let ducts: HashSet<Vector> = map
-        .iter()
-        .filter(|(_, c)| c.is_ascii_digit() || **c == '.')
-        .map(|(p, _)| p)
-        .collect();
-
-    let mut dists: HashMap<(char, char), u32> = HashMap::new();
-    for (i, &(a_p, a)) in nodes.iter().enumerate() {
-        for &(b_p, b) in &nodes[i + 1..] {
-            let dist = distance(&ducts, a_p, b_p);
-            dists.insert((a, b), dist);
-            dists.insert((b, a), dist);
+    // First compress the ducts down to a graph of the points of interest, and then fill in the
+    // distances between every pair of them.
+    let ducts = Graph::from_maze(
+        &map,
+        |_, to| map[to] != '#',
+        map.keys().filter(|&p| map[p].is_ascii_digit()),
+    );
+    let mut dists = Graph::directed();
+    for a in ducts.ids() {
+        let distances = search::distances(a, |&a, push| {
+            ducts.neighbors(a).for_each(|(b, &steps)| push(b, steps));
+        });
+        for (b, dist) in distances {
+            if a != b {
+                dists.add_edge(map[ducts[a]], map[ducts[b]], dist);
+            }
         }
     }
 
-    let nodes_len = nodes.len();
-
-    let mut route: Vec<char> = nodes.into_iter().map(|(_, c)| c).collect();
-    route.sort_unstable();
-    if return_to_start {
-        route.push('0');
-    }
-
-    let mut best_total_dist = total_dist(&dists, &route);
-    while permute(&mut route[1..nodes_len]) {
-        best_total_dist = cmp::min(best_total_dist, total_dist(&dists, &route));
-    }
-
-    best_total_dist
+    dists
+        .shortest_route(dists.id(&'0'), return_to_start)
+        .unwrap()
 }
 
 pub fn part1(input: &str) -> u32 {
@@ -1,8 +1,7 @@
-use std::collections::HashMap;
+use crate::{cycle, part::Part};
 
-use crate::part::Part;
-
-fn redistribute(banks: &mut [u8]) {
+fn redistribute(banks: &[u8]) -> Vec<u8> {
+    let mut banks = banks.to_vec();
     // max_by_key is last wins, and we want first wins, so rev
     let mut i = (0..banks.len()).rev().max_by_key(|i| banks[*i]).unwrap();
     let mut blocks = banks[i];
@@ -12,27 +11,19 @@ fn redistribute(banks: &mut [u8]) {
         i = (i + 1) % banks.len();
         banks[i] += 1;
     }
+    banks
 }
 
 fn part_(part: Part, input: &str) -> usize {
-    let mut banks = input
+    let banks: Vec<u8> = input
         .split_whitespace()
         .map(|w| w.parse().unwrap())
-        .collect::<Vec<_>>();
-
-    let mut seen = HashMap::new();
-    let mut cycle = 0;
+        .collect();
 
-    loop {
-        seen.insert(banks.clone(), cycle);
-        redistribute(&mut banks);
-        cycle += 1;
-        if let Some(prev) = seen.get(&banks) {
-            return match part {
-                Part::One => cycle,
-                Part::Two => cycle - prev,
-            };
-        }
+    let cycle = cycle::hashed(banks, |banks| redistribute(banks));
+    match part {
+        Part::One => cycle.start + cycle.len,
+        Part::Two => cycle.len,
     }
 }
 
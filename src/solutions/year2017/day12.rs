@@ -1,45 +1,31 @@
-use std::collections::{HashMap, HashSet};
+use crate::graph::Graph;
 
-use crate::search;
-
-fn parse(input: &str) -> HashMap<u32, Vec<u32>> {
-    let mut res = HashMap::new();
+fn parse(input: &str) -> Graph<u32, ()> {
+    let mut res = Graph::undirected();
     for line in input.lines() {
         let (prog, neighbors) = line.split_once(" <-> ").unwrap();
-        res.insert(
-            prog.trim().parse().unwrap(),
-            neighbors.split(", ").map(|s| s.parse().unwrap()).collect(),
-        );
+        let prog: u32 = prog.trim().parse().unwrap();
+        res.add_node(prog);
+        for neighbor in neighbors.split(", ") {
+            res.add_edge(prog, neighbor.parse().unwrap(), ());
+        }
     }
     res
 }
 
-fn search(graph: &HashMap<u32, Vec<u32>>, start: u32) -> impl Iterator<Item = u32> + '_ {
-    search::breadth_first(
-        start,
-        |&pos, push| graph[&pos].iter().copied().for_each(push),
-        search::id_filter(),
-    )
-}
-
 pub fn part1(input: &str) -> usize {
-    search(&parse(input), 0).count()
+    let graph = parse(input);
+    let zero = graph.id(&0).unwrap();
+    graph
+        .components()
+        .into_iter()
+        .find(|component| component.contains(&zero))
+        .unwrap()
+        .len()
 }
 
 pub fn part2(input: &str) -> usize {
-    let graph = parse(input);
-
-    let mut components = 0;
-    let mut unexplored: HashSet<_> = graph.keys().copied().collect();
-
-    while !unexplored.is_empty() {
-        components += 1;
-        for pos in search(&graph, *unexplored.iter().next().unwrap()) {
-            unexplored.remove(&pos);
-        }
-    }
-
-    components
+    parse(input).components().len()
 }
 
 pub fn tests() {
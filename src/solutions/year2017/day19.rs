@@ -1,7 +1,7 @@
 use crate::grid::{Grid, Vector, LEFT, RIGHT, S};
 
 fn part_(input: &str) -> (String, usize) {
-    let grid = Grid::parse(input, |_, c| c);
+    let grid = Grid::parse_padded(input, ' ', |_, c| c);
 
     let start_x = (0..grid.size.x).find(|&x| grid[[x, 0]] == '|').unwrap();
     let mut pos = Vector::new(start_x, 0);
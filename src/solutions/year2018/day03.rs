@@ -1,13 +1,12 @@
-use std::{collections::HashSet, sync::LazyLock};
+use std::sync::LazyLock;
 
 use regex::Regex;
 
-use crate::grid::{Grid, IntoVector, Vector};
+use crate::grid::{Bounds, BoxSet, IntoVector, Vector, SE};
 
 struct Claim {
     id: u32,
-    pos: Vector,
-    size: Vector,
+    area: Bounds<2>,
 }
 
 impl From<&str> for Claim {
@@ -15,47 +14,42 @@ impl From<&str> for Claim {
         static RE: LazyLock<Regex> =
             LazyLock::new(|| Regex::new(r"#(\d+) @ (\d+,\d+): (\d+x\d+)").unwrap());
         let captures = RE.captures(s).unwrap();
+        let pos: Vector = captures[2].into_vector();
+        let size: Vector = captures[3].into_vector();
         Claim {
             id: captures[1].parse().unwrap(),
-            pos: captures[2].into_vector(),
-            size: captures[3].into_vector(),
+            area: Bounds {
+                min: pos,
+                max: pos + size - SE,
+            },
         }
     }
 }
 
-pub fn part1(input: &str) -> u32 {
-    let mut res = 0;
-    let mut fabric = Grid::new(0, [1000, 1000]);
-    for claim in input.lines().map(Claim::from) {
-        for x in claim.pos.x..claim.pos.x + claim.size.x {
-            for y in claim.pos.y..claim.pos.y + claim.size.y {
-                fabric[[x, y]] += 1;
-                if fabric[[x, y]] == 2 {
-                    res += 1;
-                }
+pub fn part1(input: &str) -> i64 {
+    let claims: Vec<Claim> = input.lines().map(Claim::from).collect();
+    let mut overlap = BoxSet::new();
+    for (i, a) in claims.iter().enumerate() {
+        for b in &claims[i + 1..] {
+            if let Some(intersection) = a.area.intersection(&b.area) {
+                overlap.insert(intersection);
             }
         }
     }
-    res
+    overlap.volume()
 }
 
 pub fn part2(input: &str) -> u32 {
-    let mut disjoint = HashSet::new();
-    let mut fabric = Grid::new(None, [1000, 1000]);
-    for claim in input.lines().map(Claim::from) {
-        disjoint.insert(claim.id);
-        for x in claim.pos.x..claim.pos.x + claim.size.x {
-            for y in claim.pos.y..claim.pos.y + claim.size.y {
-                if let Some(id) = fabric[[x, y]] {
-                    disjoint.remove(&id);
-                    disjoint.remove(&claim.id);
-                }
-                fabric[[x, y]] = Some(claim.id);
-            }
-        }
-    }
-    assert_eq!(disjoint.len(), 1);
-    disjoint.into_iter().next().unwrap()
+    let claims: Vec<Claim> = input.lines().map(Claim::from).collect();
+    claims
+        .iter()
+        .find(|a| {
+            claims
+                .iter()
+                .all(|b| a.id == b.id || !a.area.intersects(&b.area))
+        })
+        .unwrap()
+        .id
 }
 
 pub fn tests() {
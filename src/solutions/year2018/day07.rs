@@ -1,26 +1,25 @@
-use std::collections::HashMap;
+use std::collections::HashSet;
 
 use regex::bytes::Regex;
 
-fn parse(input: &str) -> HashMap<char, Vec<char>> {
-    let mut deps: HashMap<char, Vec<char>> = HashMap::new();
+use crate::graph::Graph;
+
+fn parse(input: &str) -> Graph<char, ()> {
+    let mut res = Graph::directed();
     for captures in Regex::new(r"Step (.) must be finished before step (.) can begin")
         .unwrap()
         .captures_iter(input.as_bytes())
     {
-        let a = captures[1][0].into();
-        let b = captures[2][0].into();
-        deps.entry(a).or_default();
-        deps.entry(b).or_default().push(a);
+        res.add_edge(captures[1][0].into(), captures[2][0].into(), ());
     }
-    deps
+    res
 }
 
-fn next(deps: &HashMap<char, Vec<char>>, done: &str) -> Option<char> {
-    deps.keys()
+fn next(steps: &Graph<char, ()>, todo: &HashSet<usize>, done: &HashSet<usize>) -> Option<usize> {
+    todo.iter()
         .copied()
-        .filter(|step| deps[step].iter().all(|&s| done.contains(s)))
-        .min()
+        .filter(|&step| steps.predecessors(step).all(|(s, ())| done.contains(&s)))
+        .min_by_key(|&step| steps[step])
 }
 
 fn time(base_time: u32, step: char) -> u32 {
@@ -28,16 +27,17 @@ fn time(base_time: u32, step: char) -> u32 {
 }
 
 fn part2_(workers: usize, base_time: u32, input: &str) -> u32 {
-    let mut deps = parse(input);
-    let mut done = String::new();
-    let mut in_progress: Vec<(char, u32)> = Vec::new();
+    let steps = parse(input);
+    let mut todo: HashSet<usize> = steps.ids().collect();
+    let mut done = HashSet::new();
+    let mut in_progress: Vec<(usize, u32)> = Vec::new();
     let mut t = 0;
 
-    while !deps.is_empty() || !in_progress.is_empty() {
+    while !todo.is_empty() || !in_progress.is_empty() {
         while in_progress.len() < workers {
-            if let Some(step) = next(&deps, &done) {
-                deps.remove(&step);
-                in_progress.push((step, time(base_time, step)));
+            if let Some(step) = next(&steps, &todo, &done) {
+                todo.remove(&step);
+                in_progress.push((step, time(base_time, steps[step])));
             } else {
                 break;
             }
@@ -49,7 +49,7 @@ fn part2_(workers: usize, base_time: u32, input: &str) -> u32 {
             if timer > jump {
                 next_in_progress.push((step, timer - jump));
             } else {
-                done.push(step);
+                done.insert(step);
             }
         }
 
@@ -61,13 +61,13 @@ fn part2_(workers: usize, base_time: u32, input: &str) -> u32 {
 }
 
 pub fn part1(input: &str) -> String {
-    let mut deps = parse(input);
-    let mut done = String::new();
-    while let Some(step) = next(&deps, &done) {
-        deps.remove(&step);
-        done.push(step);
-    }
-    done
+    let steps = parse(input);
+    steps
+        .toposort()
+        .unwrap()
+        .into_iter()
+        .map(|step| steps[step])
+        .collect()
 }
 
 pub fn part2(input: &str) -> u32 {
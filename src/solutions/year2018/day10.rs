@@ -1,6 +1,9 @@
 use regex::Regex;
 
-use crate::grid::{Bounds, IntoVector, Vector};
+use crate::{
+    grid::{Bounds, IntoVector, Vector},
+    search,
+};
 
 struct Light {
     position: Vector,
@@ -18,40 +21,26 @@ fn parse(input: &str) -> Vec<Light> {
         .collect()
 }
 
-fn tick(lights: &mut [Light]) {
-    for light in lights {
-        light.position += light.velocity;
-    }
+fn positions(lights: &[Light], t: i32) -> impl Iterator<Item = Vector> + Clone + '_ {
+    lights
+        .iter()
+        .map(move |light| light.position + light.velocity * t)
 }
 
-fn untick(lights: &mut [Light]) {
-    for light in lights {
-        light.position -= light.velocity;
-    }
-}
-
-fn height(lights: &[Light]) -> i32 {
-    let bounds = Bounds::new(lights.iter().map(|light| light.position));
+fn height(lights: &[Light], t: i32) -> i32 {
+    let bounds = Bounds::new(positions(lights, t));
     bounds.max.y - bounds.min.y + 1
 }
 
-fn part_(input: &str) -> (&str, usize) {
-    let mut lights = parse(input);
-    let mut h = height(&lights);
-    for t in 0.. {
-        tick(&mut lights);
-        let next_h = height(&lights);
-        if next_h < h {
-            h = next_h;
-        } else {
-            untick(&mut lights);
-            return (
-                crate::ocr::parse(lights.iter().map(|light| light.position)),
-                t,
-            );
-        }
-    }
-    unreachable!();
+fn part_(input: &str) -> (&str, i32) {
+    let lights = parse(input);
+    // The height is the difference between a max and a min of linear functions of time, so it's
+    // convex and we can ternary search for its minimum. Once it's back above where it started we
+    // must be past the minimum, which gives the search its upper bound.
+    let start = height(&lights, 0);
+    let end = search::exponential(0, |t| height(&lights, t) > start);
+    let t = search::ternary(0, end, |t| height(&lights, t));
+    (crate::ocr::parse(positions(&lights, t)), t)
 }
 
 pub fn part1(input: &str) -> &str {
@@ -59,7 +48,7 @@ pub fn part1(input: &str) -> &str {
     res
 }
 
-pub fn part2(input: &str) -> usize {
+pub fn part2(input: &str) -> i32 {
     let (_, t) = part_(input);
     t
 }
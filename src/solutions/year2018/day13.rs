@@ -1,6 +1,9 @@
+use std::time::Duration;
+
 use crate::{
     grid::{Grid, IntoVector, Vector, LEFT, RIGHT},
     part::Part,
+    render::{Cell, Palette, Recorder, GREY, RED, YELLOW},
 };
 
 enum Tile {
@@ -73,18 +76,21 @@ fn parse(input: &str) -> (Grid<Tile>, Vec<Cart>) {
     (track, carts)
 }
 
-fn tick(part: Part, track: &Grid<Tile>, carts: &mut Vec<Cart>) -> Option<Vector> {
+// Returns where carts crashed. In part 1 we stop at the first crash.
+fn tick(part: Part, track: &Grid<Tile>, carts: &mut Vec<Cart>) -> Vec<Vector> {
     carts.sort_unstable_by_key(|c| [c.pos.y, c.pos.x]);
 
+    let mut crashes = Vec::new();
     let mut i = 0;
     while i < carts.len() {
         let pos = carts[i].pos + carts[i].dir;
         if let Some(j) = carts.iter().position(|c| c.pos == pos) {
             match part {
                 Part::One => {
-                    return Some(pos);
+                    return vec![pos];
                 }
                 Part::Two => {
+                    crashes.push(pos);
                     carts.remove(j);
                     if j < i {
                         i -= 1;
@@ -99,13 +105,49 @@ fn tick(part: Part, track: &Grid<Tile>, carts: &mut Vec<Cart>) -> Option<Vector>
         i += 1;
     }
 
-    None
+    crashes
+}
+
+/// Record the carts going round the track until only one is left, with the sites of crashes so far
+/// highlighted.
+pub fn record(input: &str) -> Recorder {
+    let palette = Palette::new([
+        ('-', GREY),
+        ('|', GREY),
+        ('/', GREY),
+        ('\\', GREY),
+        ('+', YELLOW),
+    ]);
+    let (track, mut carts) = parse(input);
+    let background = palette.paint(&Grid::parse(input, |_, c| match c {
+        '^' | 'v' => '|',
+        '<' | '>' => '-',
+        _ => c,
+    }));
+    let mut crashes = Vec::new();
+    let mut recorder = Recorder::new();
+    loop {
+        let mut frame = background.clone();
+        for cart in &carts {
+            let arrow = "^>v<"
+                .chars()
+                .find(|&c| c.into_vector() == cart.dir)
+                .unwrap();
+            frame[cart.pos] = Cell::new(arrow).fg(RED);
+        }
+        frame.highlight(crashes.iter().copied(), RED);
+        recorder.record(&frame, Duration::from_millis(50));
+        if carts.len() < 2 {
+            return recorder;
+        }
+        crashes.extend(tick(Part::Two, &track, &mut carts));
+    }
 }
 
 pub fn part1(input: &str) -> String {
     let (track, mut carts) = parse(input);
     loop {
-        if let Some(crash) = tick(Part::One, &track, &mut carts) {
+        if let Some(crash) = tick(Part::One, &track, &mut carts).first() {
             return format!("{},{}", crash.x, crash.y);
         }
     }
@@ -134,8 +176,8 @@ pub fn tests() {
         ),
         "7,3",
     );
-    assert_eq!(
-        part2("/>-<\\  \n|   |  \n| /<+-\\\n| | | v\n\\>+</ |\n  |   ^\n  \\<->/"),
-        "6,4",
-    );
+    let example2 = "/>-<\\  \n|   |  \n| /<+-\\\n| | | v\n\\>+</ |\n  |   ^\n  \\<->/";
+    assert_eq!(part2(example2), "6,4");
+    // The start, and a frame for each of the three ticks it takes to get down to one cart.
+    assert_eq!(record(example2).len(), 4);
 }
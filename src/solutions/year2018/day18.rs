@@ -1,6 +1,7 @@
-use std::collections::HashMap;
-
-use crate::grid::{Adjacent, Grid};
+use crate::{
+    cycle,
+    grid::{Adjacent, Grid},
+};
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 enum Tile {
@@ -47,35 +48,24 @@ fn tick(state: &Grid<Tile>) -> Grid<Tile> {
     res
 }
 
-fn find_cycle(mut state: Grid<Tile>) -> (usize, usize) {
-    let mut seen = HashMap::new();
-    let mut min = 0;
-    while !seen.contains_key(&state) {
-        let next_state = tick(&state);
-        seen.insert(state, min);
-        state = next_state;
-        min += 1;
-    }
-    (seen[&state], min)
-}
-
-fn part_(mins: usize, mut state: Grid<Tile>) -> usize {
-    for _ in 0..mins {
-        state = tick(&state);
-    }
+fn resource_value(state: &Grid<Tile>) -> usize {
     let trees = state.values().filter(|&&tile| tile == Tile::Trees);
     let lumberyards = state.values().filter(|&&tile| tile == Tile::Lumberyard);
     trees.count() * lumberyards.count()
 }
 
 pub fn part1(input: &str) -> usize {
-    part_(10, parse(input))
+    let mut state = parse(input);
+    for _ in 0..10 {
+        state = tick(&state);
+    }
+    resource_value(&state)
 }
 
 pub fn part2(input: &str) -> usize {
     let state = parse(input);
-    let (start, end) = find_cycle(state.clone());
-    part_(start + (1_000_000_000 - start) % (end - start), state)
+    let cycle = cycle::brent(state.clone(), tick);
+    resource_value(&cycle.state_at(state, tick, 1_000_000_000))
 }
 
 pub fn tests() {
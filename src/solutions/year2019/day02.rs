@@ -1,4 +1,4 @@
-use crate::{intcode::VM, unbounded_vec, unbounded_vec::UnboundedVec};
+use crate::intcode::{memory::Memory, VM};
 
 fn run(mut vm: VM, noun: i64, verb: i64) -> i64 {
     vm.mem[1] = noun;
@@ -24,21 +24,18 @@ pub fn part2(input: &str) -> i64 {
 }
 
 pub fn tests() {
-    fn assert_mem(input: &str, expected: &UnboundedVec<i64>) {
+    fn assert_mem(input: &str, expected: &[i64]) {
         let mut vm = VM::new(input);
         vm.halt();
-        assert_eq!(&vm.mem, expected);
+        assert_eq!(vm.mem, Memory::new(expected));
     }
 
     assert_mem(
         "1,9,10,3,2,3,11,0,99,30,40,50",
-        &unbounded_vec![3500, 9, 10, 70, 2, 3, 11, 0, 99, 30, 40, 50; 0],
-    );
-    assert_mem("1,0,0,0,99", &unbounded_vec![2, 0, 0, 0, 99; 0]);
-    assert_mem("2,3,0,3,99", &unbounded_vec![2, 3, 0, 6, 99; 0]);
-    assert_mem("2,4,4,5,99,0", &unbounded_vec![2, 4, 4, 5, 99, 9801; 0]);
-    assert_mem(
-        "1,1,1,4,99,5,6,0,99",
-        &unbounded_vec![30, 1, 1, 4, 2, 5, 6, 0, 99; 0],
+        &[3500, 9, 10, 70, 2, 3, 11, 0, 99, 30, 40, 50],
     );
+    assert_mem("1,0,0,0,99", &[2, 0, 0, 0, 99]);
+    assert_mem("2,3,0,3,99", &[2, 3, 0, 6, 99]);
+    assert_mem("2,4,4,5,99,0", &[2, 4, 4, 5, 99, 9801]);
+    assert_mem("1,1,1,4,99,5,6,0,99", &[30, 1, 1, 4, 2, 5, 6, 0, 99]);
 }
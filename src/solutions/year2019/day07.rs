@@ -1,48 +1,36 @@
 use crate::{
     combinatorics::permute,
-    intcode::{State, VM},
+    intcode::{
+        network::{Network, Protocol},
+        VM,
+    },
 };
 
 fn amplify1(vm: &VM, phases: &[i64]) -> i64 {
-    let mut signal = 0;
-    for &phase in phases {
+    phases.iter().fold(0, |signal, &phase| {
         let mut amp = vm.clone();
-        amp.input(phase);
-        amp.input(signal);
-        signal = amp.output();
+        let signal = amp.run_with([phase, signal])[0];
         amp.halt();
-    }
-    signal
+        signal
+    })
 }
 
 fn amplify2(vm: &VM, phases: &[i64]) -> i64 {
-    let mut amps = vec![vm.clone(); 5];
-    for i in 0..5 {
-        amps[i].input(phases[i]);
-    }
-    amps[0].input(0);
-
-    let mut i = 0;
-    loop {
-        if amps[i].state() == State::Output {
-            match amps[(i + 1) % 5].state() {
-                State::Input => {
-                    let signal = amps[i].output();
-                    amps[(i + 1) % 5].input(signal);
-                }
-                State::Output => {}
-                State::Halt => {
-                    assert_eq!(i, 4, "expected final output to come from amp E");
-                    let signal = amps[i].output();
-                    for amp in &mut amps {
-                        amp.halt();
-                    }
-                    return signal;
-                }
-            }
-        }
-        i = (i + 1) % 5;
+    let mut network = Network::new(
+        vec![vm.clone(); 5],
+        Protocol {
+            message_len: 1,
+            decode: |from, signal| ((from + 1) % 5, signal[0]),
+            encode: |&signal| vec![signal],
+            idle_input: None,
+        },
+    );
+    for (i, &phase) in phases.iter().enumerate() {
+        network.vm_mut(i).input(phase);
     }
+    network.send(0, 0);
+    network.run_until(|| false);
+    *network.inbox(0).back().unwrap()
 }
 
 pub fn part1(input: &str) -> i64 {
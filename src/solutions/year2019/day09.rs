@@ -1,6 +1,12 @@
 use std::fmt::Write;
 
-use crate::intcode::{State, VM};
+use crate::intcode::{
+    self,
+    asm::{self, Op},
+    debug::{self, Debugger, Stop},
+    snapshot::Snapshot,
+    State, VmErrorKind, VM,
+};
 
 pub fn part1(input: &str) -> i64 {
     let mut vm = VM::new(input);
@@ -18,6 +24,80 @@ pub fn part2(input: &str) -> i64 {
     res
 }
 
+/// Debug the countdown program from `tests`.
+fn debug_countdown(countdown: &str) {
+    // Wait for input, catch the count being written and the print routine being called, trace one
+    // loop, then stop on the halt.
+    let mut debugger = Debugger::new(VM::new(countdown));
+    assert_eq!(debugger.run(), Stop::Input);
+    debugger.input.push_back(2);
+    debugger.watchpoints.insert(24);
+    assert_eq!(
+        debugger.run(),
+        Stop::Watchpoint {
+            addr: 24,
+            old: 0,
+            new: 2
+        }
+    );
+    debugger.breakpoints.insert(19);
+    assert_eq!(debugger.run(), Stop::Breakpoint(19));
+    assert!(debugger.output.is_empty());
+    debugger.tracing = true;
+    assert_eq!(
+        debugger.run(),
+        Stop::Watchpoint {
+            addr: 24,
+            old: 2,
+            new: 1
+        }
+    );
+    assert_eq!(debugger.output, [2]);
+    let ips: Vec<usize> = debugger.trace.iter().map(|entry| entry.ip).collect();
+    assert_eq!(ips, [19, 21, 11]);
+    debugger.watchpoints.clear();
+    debugger.breakpoints.clear();
+    debugger.op_breakpoints.insert(Op::Halt);
+    assert_eq!(debugger.run(), Stop::OpBreakpoint(Op::Halt));
+    assert_eq!(debugger.run(), Stop::Halt);
+    assert_eq!(debugger.output, [2, 1]);
+
+    // The same again from the REPL.
+    let mut debugger = Debugger::new(VM::new(countdown));
+    let script = "b 19\ni 2\nc\nc\nb 19\nw 24\nt\nc\nt\nc\nm 24 3\nq\nc\n";
+    let mut transcript = Vec::new();
+    debug::repl(&mut debugger, script.as_bytes(), &mut transcript).unwrap();
+    let transcript = String::from_utf8(transcript).unwrap();
+    // Skip the help, which ends with the command to quit.
+    let session = transcript.split_once("quit\n> ").unwrap().1;
+    assert_eq!(
+        session,
+        [
+            "breakpoint set",
+            "> > breakpoint at 19",
+            "ip=19 r=25 out [24]",
+            "> 2",
+            "breakpoint at 19",
+            "ip=19 r=25 out [24]",
+            "> breakpoint cleared",
+            "> watchpoint set",
+            "> tracing on",
+            "> 1",
+            "[24] 1 -> 0",
+            "ip=15 r=25 jt [24], #4",
+            ">     19 r=25     out [24]",
+            "    21 r=25     jf #0, [r]",
+            "    11 r=25     add [24], #-1, [24]",
+            "tracing off",
+            "> halted",
+            "ip=18 r=25 hlt",
+            "> [24] 0, 11, 0",
+            "> ",
+        ]
+        .join("\n")
+    );
+}
+
 pub fn tests() {
     let quine = "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99";
     let mut vm = VM::new(quine);
@@ -27,6 +107,10 @@ pub fn tests() {
     }
     assert_eq!(output, quine);
     vm.halt();
+    assert_eq!(
+        asm::assemble(&asm::disassemble(&intcode::parse(quine))).as_deref(),
+        Ok(quine)
+    );
 
     let mut vm = VM::new("1102,34915192,34915192,7,4,7,99,0");
     assert_eq!(vm.output(), 1_219_070_632_396_864);
@@ -35,4 +119,98 @@ pub fn tests() {
     let mut vm = VM::new("104,1125899906842624,99");
     assert_eq!(vm.output(), 1_125_899_906_842_624);
     vm.halt();
+
+    // Count down from the input to one, via a call so that the disassembler has to find the return
+    // address for itself.
+    let countdown = asm::assemble(
+        "
+            arb #stack
+            in [count]
+        loop:
+            add #next, #0, [r]
+            jt #1, #print
+        next:
+            add [count], #-1, [count]
+            jt [count], #loop
+            hlt
+        print:
+            out [count]
+            jf #0, [r]
+        count:
+            data 0
+        stack:
+        ",
+    )
+    .unwrap();
+    let mut vm = VM::new(&countdown);
+    vm.input(3);
+    assert_eq!([vm.output(), vm.output(), vm.output()], [3, 2, 1]);
+    vm.halt();
+    let listing = asm::disassemble(&intcode::parse(&countdown));
+    assert!(listing.contains("L11:\n    add [24], #-1, [24]"));
+    assert_eq!(asm::assemble(&listing).as_ref(), Ok(&countdown));
+
+    debug_countdown(&countdown);
+
+    // Jump in to the first operand of the add, which happens to be a halt.
+    let overlapping = "1101,99,0,9,1105,1,1,99";
+    let listing = asm::disassemble(&intcode::parse(overlapping));
+    assert!(listing.contains("jt #1, #1 "));
+    assert!(!listing.contains("L1"));
+    assert_eq!(asm::assemble(&listing).as_deref(), Ok(overlapping));
+
+    // Part way through the countdown, with something high up in memory too, save and restore the
+    // VM both ways.
+    let mut vm = VM::new(&countdown);
+    vm.input(3);
+    assert_eq!(vm.output(), 3);
+    vm.mem[1_000_000] = 42;
+    let snapshot = vm.snapshot();
+    assert_eq!(vm.output(), 2);
+    for snapshot in [
+        Snapshot::from_json(&snapshot.to_json()).unwrap(),
+        Snapshot::from_bytes(&snapshot.to_bytes()).unwrap(),
+    ] {
+        let mut restored = VM::new("99");
+        restored.restore(&snapshot);
+        assert_eq!(restored.mem[1_000_000], 42);
+        assert_eq!([restored.output(), restored.output()], [2, 1]);
+        restored.halt();
+    }
+    assert_eq!(vm.output(), 1);
+    vm.halt();
+
+    // The loop body runs once per number, and the count is read and written each time.
+    let mut vm = VM::new(&countdown);
+    vm.trace();
+    vm.input(3);
+    assert_eq!([vm.output(), vm.output(), vm.output()], [3, 2, 1]);
+    vm.halt();
+    let coverage = vm.coverage().unwrap();
+    assert_eq!(coverage.executed[&11], 3);
+    assert_eq!(coverage.writes[&24], 4);
+    assert_eq!(coverage.hottest(1), [(4, 3)]);
+    let report = coverage.report(&intcode::parse(&countdown));
+    assert!(report.contains("; 11              3 #\n"));
+    assert!(report.contains("; 24          r9 w4 ##\n"));
+    assert_eq!(asm::assemble(&report), Ok(countdown));
+
+    // Arithmetic that doesn't fit in 64 bits is an error rather than a panic or a wrong answer.
+    let overflow = |prog: &str| VM::new(prog).try_state().map_err(|err| (err.ip, err.kind));
+    assert_eq!(
+        overflow("1101,9223372036854775807,1,0,99"),
+        Err((0, VmErrorKind::Overflow(i64::MAX, 1)))
+    );
+    assert_eq!(
+        overflow("1102,4611686018427387904,-3,0,99"),
+        Err((0, VmErrorKind::Overflow(1 << 62, -3)))
+    );
+    assert_eq!(
+        overflow("109,-9223372036854775807,109,-2,99"),
+        Err((2, VmErrorKind::Overflow(-i64::MAX, -2)))
+    );
+    assert_eq!(
+        overflow("109,9223372036854775807,22201,1,0,0,99"),
+        Err((2, VmErrorKind::Overflow(1, i64::MAX)))
+    );
 }
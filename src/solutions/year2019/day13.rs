@@ -1,4 +1,19 @@
-use crate::intcode::{State, VM};
+use crate::{
+    intcode::{State, VM},
+    render::{Cell, BLUE, GREY, RED, YELLOW},
+};
+
+/// How to draw a tile, or `None` if it isn't one of the five the game uses.
+pub fn tile(id: i64) -> Option<Cell> {
+    match id {
+        0 => Some(Cell::new(' ')),
+        1 => Some(Cell::new('#').fg(GREY)),
+        2 => Some(Cell::new('=').fg(BLUE)),
+        3 => Some(Cell::new('-').fg(YELLOW)),
+        4 => Some(Cell::new('o').fg(RED)),
+        _ => None,
+    }
+}
 
 pub fn play<F>(input: &str, mut handle_output: F) -> i64
 where
@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use crate::search;
+use crate::search::{self, MonotoneError};
 
 struct Reaction<'a> {
     quantity: i64,
@@ -66,6 +66,32 @@ pub fn part2(input: &str) -> i64 {
     search::exponential(1, |i| cost(&reactions, i) > 1_000_000_000_000) - 1
 }
 
+// The other monotone searches agree with part 2, report errors in terms of the predicate they were
+// given, and never ask about the same amount of fuel twice.
+fn check_searches(input: &str, fuel: i64) {
+    let reactions = parse(input);
+    let affordable = |i| cost(&reactions, i) <= 1_000_000_000_000;
+    assert_eq!(search::binary_last(1, fuel * 10, affordable), fuel);
+    assert_eq!(
+        search::try_binary_last(fuel + 1, fuel * 10, affordable),
+        Err(MonotoneError::FalseAtLow(fuel + 1))
+    );
+    assert_eq!(
+        search::try_binary_last(1, fuel, affordable),
+        Err(MonotoneError::TrueAtHigh(fuel))
+    );
+    for guess in [1, fuel, fuel + 1, fuel * 2] {
+        let mut asked = Vec::new();
+        let first_unaffordable = search::exponential_both(guess, |i| {
+            asked.push(i);
+            !affordable(i)
+        });
+        assert_eq!(first_unaffordable, fuel + 1);
+        asked.sort_unstable();
+        assert!(asked.windows(2).all(|w| w[0] != w[1]));
+    }
+}
+
 pub fn tests() {
     let example0 = &[
         "10 ORE => 10 A",
@@ -143,4 +169,5 @@ pub fn tests() {
     assert_eq!(part2(example2), 82_892_753);
     assert_eq!(part2(example3), 5_586_022);
     assert_eq!(part2(example4), 460_664);
+    check_searches(example2, 82_892_753);
 }
@@ -1,7 +1,8 @@
 use std::collections::{BTreeSet, HashMap};
 
 use crate::{
-    grid::{Adjacent, Grid, Vector, E, N, NE, NW, S, SE, SW, W, Z},
+    graph::{Corridor, Graph},
+    grid::{Grid, E, N, NE, NW, S, SE, SW, W, Z},
     search,
 };
 
@@ -21,72 +22,32 @@ fn key(door: char) -> char {
     door.to_ascii_lowercase()
 }
 
-// Path represents a walk to another key, the steps and keys required.
-struct Path {
-    dest: char,
-    steps: usize,
-    keys: BTreeSet<char>,
-}
-
-fn reachable(map: &Grid<char>, pos: Vector) -> Vec<Path> {
-    #[derive(Clone)]
-    struct State {
-        pos: Vector,
-        steps: usize,
-        keys: BTreeSet<char>,
-        found_key: bool,
-    }
-
-    search::breadth_first(
-        State {
-            pos,
-            steps: 0,
-            keys: BTreeSet::new(),
-            found_key: false,
-        },
-        |state, push| {
-            if state.found_key {
-                return;
-            }
-            state
-                .pos
-                .adjacent4()
-                .filter(|&v| map.get(v).is_some_and(|&tile| is_open(tile)))
-                .for_each(|pos| {
-                    let mut state = state.clone();
-                    state.pos = pos;
-                    state.steps += 1;
-                    if is_door(map[pos]) {
-                        state.keys.insert(key(map[pos]));
-                    } else if is_key(map[pos]) {
-                        state.found_key = true;
-                    }
-                    push(state);
-                });
-        },
-        search::hash_filter(|state: &State| state.pos),
-    )
-    .filter(|state| state.found_key)
-    .map(|state| Path {
-        dest: map[state.pos],
-        steps: state.steps,
-        keys: state.keys,
-    })
-    .collect()
-}
-
-fn key_graph(map: &Grid<char>) -> HashMap<char, Vec<Path>> {
-    map.iter()
-        .filter(|&(_, &tile)| is_key(tile))
-        .map(|(pos, &tile)| (tile, reachable(map, pos)))
+// For each key, the corridors to the keys reachable from it without passing another key, each
+// with the keys required to traverse it.
+fn key_graph(map: &Grid<char>) -> HashMap<char, Vec<(char, Corridor<char>)>> {
+    let graph = Graph::from_maze_with_doors(
+        map,
+        |_, to| is_open(map[to]),
+        map.keys().filter(|&pos| is_key(map[pos])),
+        |pos| is_door(map[pos]).then(|| key(map[pos])),
+    );
+    graph
+        .ids()
+        .map(|a| {
+            let paths = graph
+                .neighbors(a)
+                .map(|(b, corridor)| (map[graph[b]], corridor.clone()))
+                .collect();
+            (map[graph[a]], paths)
+        })
         .collect()
 }
 
-fn part_(map: &Grid<char>, robots: Vec<char>) -> usize {
+fn part_(map: &Grid<char>, robots: Vec<char>) -> u32 {
     #[derive(Clone, PartialOrd, Ord, PartialEq, Eq)]
     struct State {
         robots: Vec<char>,
-        steps: usize,
+        steps: u32,
         keys: BTreeSet<char>,
     }
 
@@ -101,7 +62,7 @@ fn part_(map: &Grid<char>, robots: Vec<char>) -> usize {
     let min_path_steps = key_graph
         .values()
         .flatten()
-        .map(|path| path.steps)
+        .map(|(_, corridor)| corridor.steps)
         .min()
         .unwrap();
 
@@ -114,14 +75,14 @@ fn part_(map: &Grid<char>, robots: Vec<char>) -> usize {
         },
         move |state, push| {
             for i in 0..state.robots.len() {
-                for path in key_graph[&state.robots[i]]
+                for (dest, corridor) in key_graph[&state.robots[i]]
                     .iter()
-                    .filter(|path| path.keys.is_subset(&state.keys))
+                    .filter(|(_, corridor)| corridor.doors.is_subset(&state.keys))
                 {
                     let mut state = state.clone();
-                    state.robots[i] = path.dest;
-                    state.steps += path.steps;
-                    state.keys.insert(path.dest);
+                    state.robots[i] = *dest;
+                    state.steps += corridor.steps;
+                    state.keys.insert(*dest);
                     push(state);
                 }
             }
@@ -129,18 +90,18 @@ fn part_(map: &Grid<char>, robots: Vec<char>) -> usize {
         search::hash_filter(|state: &State| (state.robots.clone(), state.keys.clone())),
         |state| state.steps,
         // For each key left to collect, we'll have to move at least min_path_steps.
-        move |state| (final_key_count - state.keys.len()) * min_path_steps,
+        move |state| u32::try_from(final_key_count - state.keys.len()).unwrap() * min_path_steps,
     )
     .find(|state| state.keys.len() == final_key_count)
     .unwrap()
     .steps
 }
 
-pub fn part1(input: &str) -> usize {
+pub fn part1(input: &str) -> u32 {
     part_(&Grid::parse(input, |_, tile| tile), vec!['@'])
 }
 
-pub fn part2(input: &str) -> usize {
+pub fn part2(input: &str) -> u32 {
     let mut map = Grid::parse(input, |_, tile| tile);
     let (start, _) = map.iter().find(|&(_, &tile)| tile == '@').unwrap();
     for dir in [Z, N, E, S, W] {
@@ -152,3 +113,41 @@ pub fn part2(input: &str) -> usize {
     map[start + NW] = '3';
     part_(&map, vec!['0', '1', '2', '3'])
 }
+
+pub fn tests() {
+    let example0 = ["#########", "#b.A.@.a#", "#########"].join("\n");
+    let example1 = [
+        "########################",
+        "#f.D.E.e.C.b.A.@.a.B.c.#",
+        "######################.#",
+        "#d.....................#",
+        "########################",
+    ]
+    .join("\n");
+    let example2 = [
+        "#################",
+        "#i.G..c...e..H.p#",
+        "########.########",
+        "#j.A..b...f..D.o#",
+        "########@########",
+        "#k.E..a...g..B.n#",
+        "########.########",
+        "#l.F..d...h..C.m#",
+        "#################",
+    ]
+    .join("\n");
+    let example3 = [
+        "#############",
+        "#DcBa.#.GhKl#",
+        "#.###...#I###",
+        "#e#d#.@.#j#k#",
+        "###C#...###J#",
+        "#fEbA.#.FgHi#",
+        "#############",
+    ]
+    .join("\n");
+    assert_eq!(part1(&example0), 8);
+    assert_eq!(part1(&example1), 86);
+    assert_eq!(part1(&example2), 136);
+    assert_eq!(part2(&example3), 32);
+}
@@ -1,29 +1,22 @@
-use std::io::{self, Read, Write};
+use std::io::{self, BufRead, BufReader, Read, Write};
 
 use anyhow::{Context, Result};
 
 use crate::intcode::{State, VM};
 
 pub fn play(vm: &mut VM, r: impl Read, mut w: impl Write) -> Result<Option<i64>> {
-    let mut input = r.bytes();
+    let mut lines = BufReader::new(r).lines();
     loop {
+        write!(&mut w, "{}", vm.recv_text())?;
         match vm.state() {
-            State::Input => {
-                vm.input(input.next().context("EOF")??.into());
-            }
+            State::Input => vm.send_line(&lines.next().context("EOF")??),
             State::Output => {
+                // if output isn't ASCII, assume it's the final damage reading
                 let output = vm.output();
-                if let Ok(c) = u8::try_from(output) {
-                    write!(&mut w, "{}", char::from(c))?;
-                } else {
-                    // if output won't fit in a u8, assume it's the final damage reading
-                    vm.halt();
-                    return Ok(Some(output));
-                }
-            }
-            State::Halt => {
-                return Ok(None);
+                vm.halt();
+                return Ok(Some(output));
             }
+            State::Halt => return Ok(None),
         }
     }
 }
@@ -1,106 +1,57 @@
-use std::collections::VecDeque;
-
-use crate::intcode::{State, VM};
-
-#[derive(Clone)]
-struct Computer {
-    vm: VM,
-    inbox: VecDeque<(i64, i64)>,
-}
-
-struct Network {
-    computers: Vec<Computer>,
-}
-
-impl Network {
-    fn new(input: &str) -> Self {
-        let mut computers = vec![
-            Computer {
-                vm: VM::new(input),
-                inbox: VecDeque::new()
-            };
-            50
-        ];
-        for (i, computer) in computers.iter_mut().enumerate() {
-            computer.vm.input(i.try_into().unwrap());
-        }
-        Network { computers }
-    }
-
-    fn rcv(&mut self, i: usize) -> bool {
-        if let Some((x, y)) = self.computers[i].inbox.pop_front() {
-            self.computers[i].vm.input(x);
-            self.computers[i].vm.input(y);
-            true
-        } else {
-            self.computers[i].vm.input(-1);
-            false
-        }
-    }
-
-    fn snd(&mut self, i: usize) -> Option<(i64, i64)> {
-        let dst: usize = self.computers[i].vm.output().try_into().unwrap();
-        let packet = (self.computers[i].vm.output(), self.computers[i].vm.output());
-        if dst == 255 {
-            Some(packet)
-        } else {
-            self.computers[dst].inbox.push_back(packet);
-            None
-        }
+use std::cell::Cell;
+
+use crate::intcode::{
+    network::{Event, Network, Protocol},
+    VM,
+};
+
+/// Fifty computers exchanging packets, each waiting to be told its address. Packets sent to 255 go
+/// to whatever custom node the caller adds there.
+fn network<'a>(input: &str) -> Network<'a, (i64, i64)> {
+    let mut network = Network::new(
+        vec![VM::new(input); 50],
+        Protocol {
+            message_len: 3,
+            decode: |_, packet| (usize::try_from(packet[0]).unwrap(), (packet[1], packet[2])),
+            encode: |&(x, y)| vec![x, y],
+            idle_input: Some(-1),
+        },
+    );
+    for i in 0..50 {
+        network.vm_mut(i).input(i64::try_from(i).unwrap());
     }
+    network
 }
 
 pub fn part1(input: &str) -> i64 {
-    let mut network = Network::new(input);
-    loop {
-        for i in 0..network.computers.len() {
-            match network.computers[i].vm.state() {
-                State::Input => {
-                    network.rcv(i);
-                }
-                State::Output => {
-                    if let Some(packet) = network.snd(i) {
-                        return packet.1;
-                    }
-                }
-                State::Halt => unreachable!(),
-            }
+    let first_y = Cell::new(None);
+    let mut network = network(input);
+    network.add_node(255, |event, _| {
+        if let Event::Message((_, y)) = event {
+            first_y.set(first_y.get().or(Some(y)));
         }
-    }
+    });
+    network.run_until(|| first_y.get().is_some());
+    first_y.get().unwrap()
 }
 
 pub fn part2(input: &str) -> i64 {
-    let mut network = Network::new(input);
+    let repeated_y = Cell::new(None);
     let mut nat = None;
     let mut last_y = None;
-    loop {
-        let mut idle = true;
-        for i in 0..network.computers.len() {
-            match network.computers[i].vm.state() {
-                State::Input => {
-                    if network.rcv(i) {
-                        idle = false;
-                    }
+    let mut network = network(input);
+    network.add_node(255, |event, send| match event {
+        Event::Message(packet) => nat = Some(packet),
+        Event::Idle => {
+            if let Some((x, y)) = nat {
+                if last_y == Some(y) {
+                    repeated_y.set(Some(y));
                 }
-                State::Output => {
-                    idle = false;
-                    if let Some(packet) = network.snd(i) {
-                        nat = Some(packet);
-                    }
-                }
-                State::Halt => unreachable!(),
+                last_y = Some(y);
+                send(0, (x, y));
             }
         }
-        if idle {
-            if let Some(nat) = nat {
-                if let Some(last_y) = last_y {
-                    if nat.1 == last_y {
-                        return last_y;
-                    }
-                }
-                last_y = Some(nat.1);
-                network.computers[0].inbox.push_back(nat);
-            }
-        }
-    }
+    });
+    network.run_until(|| repeated_y.get().is_some());
+    repeated_y.get().unwrap()
 }
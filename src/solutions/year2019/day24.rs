@@ -1,9 +1,9 @@
-use std::{
-    collections::{HashMap, HashSet},
-    mem,
-};
+use std::{collections::HashMap, mem};
 
-use crate::grid::{Adjacent, Grid, Vector};
+use crate::{
+    cycle,
+    grid::{Adjacent, Grid, Vector},
+};
 
 fn biodiversity(bugs: &Grid<bool>) -> u32 {
     bugs.values()
@@ -12,24 +12,23 @@ fn biodiversity(bugs: &Grid<bool>) -> u32 {
         .sum()
 }
 
-pub fn part1(input: &str) -> u32 {
-    let mut bugs = Grid::parse(input, |_, c| c == '#');
-    let mut seen = HashSet::new();
-
-    while !seen.contains(&bugs) {
-        seen.insert(bugs.clone());
-        let mut bugs_next = bugs.clone();
-        for (pos, bug) in &bugs {
-            let adjacent = pos
-                .adjacent4()
-                .filter(|&v| bugs.get(v).is_some_and(|&bug| bug))
-                .count();
-            bugs_next[pos] = adjacent == 1 || !bug && adjacent == 2;
-        }
-        bugs = bugs_next;
+fn step(bugs: &Grid<bool>) -> Grid<bool> {
+    let mut bugs_next = bugs.clone();
+    for (pos, bug) in bugs {
+        let adjacent = pos
+            .adjacent4()
+            .filter(|&v| bugs.get(v).is_some_and(|&bug| bug))
+            .count();
+        bugs_next[pos] = adjacent == 1 || !bug && adjacent == 2;
     }
+    bugs_next
+}
 
-    biodiversity(&bugs)
+pub fn part1(input: &str) -> u32 {
+    let bugs = Grid::parse(input, |_, c| c == '#');
+    // The first layout to appear twice is the one at the start of the cycle.
+    let cycle = cycle::floyd(bugs.clone(), step);
+    biodiversity(&cycle.state_at(bugs, step, cycle.start))
 }
 
 fn adjacent(bugs: &HashMap<i32, Grid<bool>>, depth: i32, pos: Vector) -> usize {
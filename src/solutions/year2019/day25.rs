@@ -1,67 +1,230 @@
-use std::io::{Read, Write};
-
-use anyhow::{Context, Result};
 use regex::Regex;
 
-use crate::intcode::{State, VM};
+use crate::{
+    intcode::{State, VM},
+    search,
+};
 
-pub fn play(prog: &str, r: impl Read, mut w: impl Write) -> Result<()> {
-    let mut vm = VM::new(prog);
-    let mut input = r.bytes();
+/// Instructions the game may run in response to one command before we decide it's stuck.
+const BUDGET: u64 = 5_000_000;
+
+/// How the game responded to a command.
+enum Response {
+    /// Printed this, and is waiting for the next command.
+    Command(String),
+    /// Printed this, and ended, which happens when you win, or die.
+    Ended(String),
+    /// Went into an infinite loop, or crashed.
+    Stuck,
+}
+
+fn respond(vm: &mut VM) -> Response {
+    vm.set_budget(Some(BUDGET));
+    let mut text = String::new();
     loop {
-        match vm.state() {
-            State::Input => {
-                vm.input(input.next().context("EOF")??.into());
-            }
-            State::Output => {
-                write!(&mut w, "{}", char::from(u8::try_from(vm.output())?))?;
+        match vm.try_state() {
+            Ok(State::Input) => return Response::Command(text),
+            Ok(State::Output) => match u8::try_from(vm.output()) {
+                Ok(c) => text.push(char::from(c)),
+                Err(_) => return Response::Stuck,
+            },
+            Ok(State::Halt) => return Response::Ended(text),
+            Err(_) => return Response::Stuck,
+        }
+    }
+}
+
+fn command(vm: &mut VM, command: &str) -> Response {
+    vm.send_line(command);
+    respond(vm)
+}
+
+/// Run a command which is known to be safe.
+fn must(vm: &mut VM, cmd: &str) -> String {
+    match command(vm, cmd) {
+        Response::Command(text) => text,
+        _ => panic!("{cmd} ended the game"),
+    }
+}
+
+#[derive(Clone)]
+struct Room {
+    name: String,
+    doors: Vec<String>,
+    items: Vec<String>,
+}
+
+/// The last room described in some output. Moving into a room prints its description, but being
+/// thrown out of one prints both.
+fn parse_room(text: &str) -> Option<Room> {
+    let mut room = None;
+    let mut list = None;
+    for line in text.lines() {
+        if let Some(name) = line.strip_prefix("== ").and_then(|s| s.strip_suffix(" ==")) {
+            room = Some(Room {
+                name: name.to_string(),
+                doors: Vec::new(),
+                items: Vec::new(),
+            });
+        } else if line == "Doors here lead:" || line == "Items here:" {
+            list = Some(line);
+        } else if let (Some(room), Some(entry)) = (&mut room, line.strip_prefix("- ")) {
+            match list {
+                Some("Doors here lead:") => room.doors.push(entry.to_string()),
+                Some("Items here:") => room.items.push(entry.to_string()),
+                _ => {}
             }
-            State::Halt => {
-                return Ok(());
+        } else {
+            list = None;
+        }
+    }
+    room
+}
+
+fn opposite(dir: &str) -> &'static str {
+    match dir {
+        "north" => "south",
+        "south" => "north",
+        "east" => "west",
+        "west" => "east",
+        _ => panic!("unknown direction: {dir}"),
+    }
+}
+
+/// The droid in a room, having got there by following `path` from the start.
+#[derive(Clone)]
+struct Droid {
+    vm: VM,
+    room: Room,
+    path: Vec<String>,
+}
+
+impl Droid {
+    /// Whether it's safe to pick up an item here: the game goes on, and we can still leave.
+    fn can_take(&self, item: &str) -> bool {
+        let mut vm = self.vm.clone();
+        if !matches!(
+            command(&mut vm, &format!("take {item}")),
+            Response::Command(_)
+        ) {
+            return false;
+        }
+        // Go back the way we came, which is sure not to be the pressure-sensitive floor.
+        let dir = self
+            .path
+            .last()
+            .map_or(self.room.doors[0].as_str(), |dir| opposite(dir));
+        match command(&mut vm, dir) {
+            Response::Command(text) => {
+                parse_room(&text).is_some_and(|room| room.name != self.room.name)
             }
+            _ => false,
+        }
+    }
+
+    /// Walk to the end of `path` from wherever the droid is.
+    fn walk(&mut self, path: &[String]) {
+        let shared = self
+            .path
+            .iter()
+            .zip(path)
+            .take_while(|(a, b)| a == b)
+            .count();
+        for dir in self.path[shared..].iter().rev() {
+            must(&mut self.vm, opposite(dir));
+        }
+        for dir in &path[shared..] {
+            must(&mut self.vm, dir);
         }
+        self.path = path.to_vec();
     }
 }
 
+/// Map the ship, returning the droid in every room, and the path to the security checkpoint and
+/// the direction of the pressure-sensitive floor from there.
+fn explore(prog: &str) -> (Vec<Droid>, (Vec<String>, String)) {
+    let mut vm = VM::new(prog);
+    let Response::Command(text) = respond(&mut vm) else {
+        panic!("the game didn't start");
+    };
+    let start = Droid {
+        vm,
+        room: parse_room(&text).expect("no starting room"),
+        path: Vec::new(),
+    };
+    let mut checkpoint = None;
+    let droids = search::breadth_first(
+        start,
+        |droid, push| {
+            for dir in &droid.room.doors {
+                let mut vm = droid.vm.clone();
+                let Response::Command(text) = command(&mut vm, dir) else {
+                    continue;
+                };
+                // Going onto the floor with the wrong weight gets you thrown back out.
+                if text.contains("Alert!") {
+                    checkpoint = Some((droid.path.clone(), dir.clone()));
+                    continue;
+                }
+                let mut path = droid.path.clone();
+                path.push(dir.clone());
+                push(Droid {
+                    vm,
+                    room: parse_room(&text).expect("moved into no room"),
+                    path,
+                });
+            }
+        },
+        search::hash_filter(|droid: &Droid| droid.room.name.clone()),
+    )
+    .collect();
+    (droids, checkpoint.expect("no security checkpoint"))
+}
+
 pub fn part1(prog: &str) -> u32 {
-    let mut input = [
-        "west",
-        "west",
-        "north",
-        "take space heater",
-        "south",
-        "east",
-        "south",
-        "south",
-        "take sand",
-        "north",
-        "north",
-        "east",
-        "east",
-        "take mug",
-        "east",
-        "south",
-        "east",
-        "south",
-        "take easter egg",
-        "north",
-        "west",
-        "west",
-        "south",
-        "west",
-        "south",
-        "south",
-    ]
-    .join("\n");
-    input.push('\n');
-    let mut output = Vec::new();
-
-    play(prog, input.as_bytes(), &mut output).unwrap();
-
-    Regex::new(r"You should be able to get in by typing (\d+) on the keypad at the main airlock.")
-        .unwrap()
-        .captures(&String::from_utf8(output).unwrap())
-        .unwrap()[1]
-        .parse()
-        .unwrap()
+    let (droids, (checkpoint, floor)) = explore(prog);
+
+    let mut droid = droids[0].clone();
+    let mut items = Vec::new();
+    for other in &droids {
+        for item in &other.room.items {
+            if other.can_take(item) {
+                droid.walk(&other.path);
+                must(&mut droid.vm, &format!("take {item}"));
+                items.push(item);
+            }
+        }
+    }
+    droid.walk(&checkpoint);
+
+    let password = Regex::new(r"typing (\d+) on the keypad").unwrap();
+    // Try every combination of items on the floor, skipping any that can't be right given what
+    // we've learned: a combination which is too heavy only gets heavier with more items.
+    let mut too_heavy: Vec<u32> = Vec::new();
+    let mut too_light: Vec<u32> = Vec::new();
+    for held in 0..1 << items.len() {
+        if too_heavy.iter().any(|&heavy| heavy & !held == 0)
+            || too_light.iter().any(|&light| held & !light == 0)
+        {
+            continue;
+        }
+        let mut vm = droid.vm.clone();
+        for (i, item) in items.iter().enumerate() {
+            if held & 1 << i == 0 {
+                must(&mut vm, &format!("drop {item}"));
+            }
+        }
+        match command(&mut vm, &floor) {
+            Response::Ended(text) => {
+                return password.captures(&text).expect("no password")[1]
+                    .parse()
+                    .unwrap();
+            }
+            // Droids being lighter than the detected value means we're heavier than them.
+            Response::Command(text) if text.contains("lighter") => too_heavy.push(held),
+            Response::Command(text) if text.contains("heavier") => too_light.push(held),
+            _ => panic!("unexpected response from the floor"),
+        }
+    }
+    panic!("no combination of items works")
 }
@@ -2,41 +2,30 @@ use std::ops::Div;
 
 use crate::{
     grid::{Adjacent, Grid, Vector, NW, Z},
-    search,
+    render::Cell,
+    search::{self, Stats},
 };
 
-fn search(cave: &Grid<u32>) -> u32 {
-    struct State {
-        pos: Vector,
-        cost: u32,
+fn adjacent(cave: &Grid<u32>, pos: Vector, push: &mut dyn FnMut(Vector, u32)) {
+    for a in pos.adjacent4() {
+        if let Some(&risk) = cave.get(a) {
+            push(a, risk);
+        }
     }
-    search::dijkstra(
-        State { pos: Z, cost: 0 },
-        |s, push| {
-            s.pos
-                .adjacent4()
-                .filter_map(|a| {
-                    cave.get(a).map(|risk| State {
-                        pos: a,
-                        cost: s.cost + risk,
-                    })
-                })
-                .for_each(push);
-        },
-        search::hash_filter(|s: &State| s.pos),
-        |s| s.cost,
-    )
-    .find(|s| s.pos == cave.size + NW)
-    .unwrap()
-    .cost
 }
 
-pub fn part1(input: &str) -> u32 {
-    search(&Grid::parse(input, |_, c| c.to_digit(10).unwrap()))
+fn search(cave: &Grid<u32>) -> u32 {
+    search::dijkstra_weighted(Z, |&pos, push| adjacent(cave, pos, push))
+        .find(|&(pos, _)| pos == cave.size + NW)
+        .unwrap()
+        .1
 }
 
-pub fn part2(input: &str) -> u32 {
-    let small_cave = Grid::parse(input, |_, c| c.to_digit(10).unwrap());
+fn parse(input: &str) -> Grid<u32> {
+    Grid::parse(input, |_, c| c.to_digit(10).unwrap())
+}
+
+fn expand(small_cave: &Grid<u32>) -> Grid<u32> {
     let mut big_cave = Grid::new(0, small_cave.size * 5);
     for v in big_cave.keys() {
         big_cave[v] = crate::number_theory::wrap(
@@ -46,7 +35,35 @@ pub fn part2(input: &str) -> u32 {
             10,
         );
     }
-    search(&big_cave)
+    big_cave
+}
+
+pub fn part1(input: &str) -> u32 {
+    search(&parse(input))
+}
+
+pub fn part2(input: &str) -> u32 {
+    search(&expand(&parse(input)))
+}
+
+/// Run part 2, returning the search stats and a frame showing the order in which positions were
+/// expanded.
+pub fn trace(input: &str) -> (Stats, Grid<Cell>) {
+    let cave = expand(&parse(input));
+    let stats = Stats::new();
+    let mut order = Vec::new();
+    search::dijkstra_weighted(
+        Z,
+        stats.weighted(|&pos: &Vector, push: &mut dyn FnMut(Vector, u32)| {
+            adjacent(&cave, pos, push);
+        }),
+    )
+    .inspect(|&(pos, _)| order.push(pos))
+    .find(|&(pos, _)| pos == cave.size + NW)
+    .unwrap();
+    let mut frame = Grid::from(&cave.map(|_, &risk| char::from_digit(risk, 10).unwrap()));
+    frame.trace(order);
+    (stats, frame)
 }
 
 pub fn tests() {
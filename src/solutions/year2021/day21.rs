@@ -1,7 +1,7 @@
-use std::collections::HashMap;
-
 use regex::Regex;
 
+use crate::memo::Memo;
+
 #[derive(Eq, Hash, PartialEq, Clone, Copy)]
 struct State {
     position0: usize,
@@ -50,36 +50,29 @@ pub fn part1(input: &str) -> usize {
 
 pub fn part2(input: &str) -> usize {
     // The number of universes in which each player wins from the given state.
-    fn game_inner(cache: &mut HashMap<State, (usize, usize)>, state: State) -> (usize, usize) {
-        if state.score1 >= 21 {
-            return (0, 1);
-        }
-        let mut res = (0, 0);
-        for a in 1..=3 {
-            for b in 1..=3 {
-                for c in 1..=3 {
-                    let mut s = state;
-                    s.position0 = crate::number_theory::wrap(s.position0 + a + b + c, 1, 11);
-                    s.score0 += s.position0;
-                    let wins = game(cache, s.swap());
-                    res.0 += wins.1;
-                    res.1 += wins.0;
+    fn game(memo: &mut Memo<State, (usize, usize)>, state: State) -> (usize, usize) {
+        memo.get(state, |memo| {
+            if state.score1 >= 21 {
+                return (0, 1);
+            }
+            let mut res = (0, 0);
+            for a in 1..=3 {
+                for b in 1..=3 {
+                    for c in 1..=3 {
+                        let mut s = state;
+                        s.position0 = crate::number_theory::wrap(s.position0 + a + b + c, 1, 11);
+                        s.score0 += s.position0;
+                        let wins = game(memo, s.swap());
+                        res.0 += wins.1;
+                        res.1 += wins.0;
+                    }
                 }
             }
-        }
-        res
-    }
-
-    fn game(cache: &mut HashMap<State, (usize, usize)>, state: State) -> (usize, usize) {
-        if let Some(res) = cache.get(&state) {
-            return *res;
-        }
-        let res = game_inner(cache, state);
-        cache.insert(state, res);
-        res
+            res
+        })
     }
 
-    let wins = game(&mut HashMap::new(), parse(input));
+    let wins = game(&mut Memo::new(), parse(input));
     wins.0.max(wins.1)
 }
 
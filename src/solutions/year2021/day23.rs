@@ -164,10 +164,11 @@ fn is_organized(state: &State) -> bool {
 }
 
 pub fn part1(input: &str) -> usize {
-    crate::search::dijkstra(
+    crate::search::parallel_dijkstra(
+        crate::search::threads(),
         parse(input),
         adjacent,
-        crate::search::hash_filter(|state: &State| state.amphipods.clone()),
+        |state: &State| state.amphipods.clone(),
         |state| state.energy,
     )
     .find(is_organized)
@@ -191,4 +192,48 @@ pub fn tests() {
     );
     assert_eq!(part1(example), 12521);
     assert_eq!(part2(example), 44169);
+
+    // Check the parallel searches against the sequential ones with several threads, whatever the
+    // machine. Equal cost states may come out in any order, so compare each Dijkstra search up to
+    // the answer sorted. Breadth first ignores the energy used, and should match exactly.
+    let explored = |states: &mut dyn Iterator<Item = State>| {
+        let mut explored: Vec<_> = states
+            .take_while(|state| state.energy <= 12521)
+            .map(|state| (state.energy, state.amphipods))
+            .collect();
+        explored.sort_unstable();
+        explored
+    };
+    let sequential = explored(&mut crate::search::dijkstra(
+        parse(example),
+        adjacent,
+        crate::search::hash_filter(|state: &State| state.amphipods.clone()),
+        |state| state.energy,
+    ));
+    let parallel = explored(&mut crate::search::parallel_dijkstra(
+        4,
+        parse(example),
+        adjacent,
+        |state: &State| state.amphipods.clone(),
+        |state| state.energy,
+    ));
+    assert!(sequential.len() > 1000);
+    assert!(sequential == parallel);
+
+    let amphipods = |states: &mut dyn Iterator<Item = State>| -> Vec<_> {
+        states.take(5000).map(|state| state.amphipods).collect()
+    };
+    let sequential = amphipods(&mut crate::search::breadth_first(
+        parse(example),
+        adjacent,
+        crate::search::hash_filter(|state: &State| state.amphipods.clone()),
+    ));
+    let parallel = amphipods(&mut crate::search::parallel_breadth_first(
+        4,
+        parse(example),
+        adjacent,
+        |state: &State| state.amphipods.clone(),
+    ));
+    assert!(sequential.len() == 5000);
+    assert!(sequential == parallel);
 }
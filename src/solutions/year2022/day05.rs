@@ -2,6 +2,8 @@ use std::sync::LazyLock;
 
 use regex::Regex;
 
+use crate::grid::Grid;
+
 struct Instruction {
     count: usize,
     from: usize,
@@ -14,16 +16,18 @@ fn parse(input: &str) -> (Vec<Vec<char>>, impl Iterator<Item = Instruction> + '_
 
     let (diagram, instructions) = input.split_once("\n\n").unwrap();
 
-    let mut stacks = Vec::new();
-    for (v, c) in crate::grid::scan(diagram) {
-        if c.is_ascii_uppercase() {
-            let col = usize::try_from((v.x - 1) / 4).unwrap();
-            while stacks.len() < col + 1 {
-                stacks.push(Vec::new());
-            }
-            stacks[col].insert(0, c);
-        }
-    }
+    // Editors tend to strip the trailing whitespace from the top of the diagram.
+    let diagram = Grid::parse_padded(diagram, ' ', |_, c| c);
+    let stacks = (1..diagram.size.x)
+        .step_by(4)
+        .map(|x| {
+            (0..diagram.size.y)
+                .rev()
+                .map(|y| diagram[[x, y]])
+                .filter(char::is_ascii_uppercase)
+                .collect()
+        })
+        .collect();
 
     (
         stacks,
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use crate::memo::Memo;
 
 fn parse(input: &str) -> impl Iterator<Item = (Vec<u8>, Vec<usize>)> + '_ {
     input.lines().map(|line| {
@@ -10,64 +10,54 @@ fn parse(input: &str) -> impl Iterator<Item = (Vec<u8>, Vec<usize>)> + '_ {
     })
 }
 
+// The remaining springs and groups are always suffixes of the originals, so their lengths are
+// enough to identify them.
 fn count_arrangements(
-    cache: &mut HashMap<(usize, usize), usize>,
+    memo: &mut Memo<(usize, usize), usize>,
     springs: &[u8],
     groups: &[usize],
 ) -> usize {
-    fn count_arrangements_inner(
-        cache: &mut HashMap<(usize, usize), usize>,
-        springs: &[u8],
-        groups: &[usize],
-    ) -> usize {
+    memo.get((springs.len(), groups.len()), |memo| {
         if springs.is_empty() && groups.is_empty() {
             1
         } else if springs.is_empty() {
             0
         } else {
             match springs[0] {
-                b'.' => count_arrangements(cache, &springs[1..], groups),
-                b'#' => count_arrangements_starting_group(cache, springs, groups),
+                b'.' => count_arrangements(memo, &springs[1..], groups),
+                b'#' => count_arrangements_starting_group(memo, springs, groups),
                 b'?' => {
-                    count_arrangements(cache, &springs[1..], groups)
-                        + count_arrangements_starting_group(cache, springs, groups)
+                    count_arrangements(memo, &springs[1..], groups)
+                        + count_arrangements_starting_group(memo, springs, groups)
                 }
                 _ => unreachable!(),
             }
         }
-    }
-
-    fn count_arrangements_starting_group(
-        cache: &mut HashMap<(usize, usize), usize>,
-        springs: &[u8],
-        groups: &[usize],
-    ) -> usize {
-        if groups.is_empty()
-            || springs.len() < groups[0]
-            || springs[1..groups[0]].iter().any(|&spring| spring == b'.')
-        {
-            0
-        } else if springs.len() == groups[0] {
-            count_arrangements(cache, &springs[groups[0]..], &groups[1..])
-        } else if springs[groups[0]] != b'#' {
-            count_arrangements(cache, &springs[groups[0] + 1..], &groups[1..])
-        } else {
-            0
-        }
-    }
+    })
+}
 
-    let key = (springs.len(), groups.len());
-    if let Some(&res) = cache.get(&key) {
-        return res;
+fn count_arrangements_starting_group(
+    memo: &mut Memo<(usize, usize), usize>,
+    springs: &[u8],
+    groups: &[usize],
+) -> usize {
+    if groups.is_empty()
+        || springs.len() < groups[0]
+        || springs[1..groups[0]].iter().any(|&spring| spring == b'.')
+    {
+        0
+    } else if springs.len() == groups[0] {
+        count_arrangements(memo, &springs[groups[0]..], &groups[1..])
+    } else if springs[groups[0]] != b'#' {
+        count_arrangements(memo, &springs[groups[0] + 1..], &groups[1..])
+    } else {
+        0
     }
-    let res = count_arrangements_inner(cache, springs, groups);
-    cache.insert(key, res);
-    res
 }
 
 pub fn part1(input: &str) -> usize {
     parse(input)
-        .map(|(springs, groups)| count_arrangements(&mut HashMap::new(), &springs, &groups))
+        .map(|(springs, groups)| count_arrangements(&mut Memo::new(), &springs, &groups))
         .sum()
 }
 
@@ -79,7 +69,7 @@ pub fn part2(input: &str) -> usize {
                 unfolded.push(b'?');
                 unfolded.extend_from_slice(&springs);
             }
-            count_arrangements(&mut HashMap::new(), &unfolded, &groups.repeat(5))
+            count_arrangements(&mut Memo::new(), &unfolded, &groups.repeat(5))
         })
         .sum()
 }
@@ -1,9 +1,7 @@
 use crate::grid::Grid;
 
-fn parse(input: &str) -> impl Iterator<Item = Grid<bool>> + '_ {
-    input
-        .split("\n\n")
-        .map(|pattern| Grid::parse(pattern, |_, c| c == '#'))
+fn parse(input: &str) -> Vec<Grid<bool>> {
+    Grid::parse_blocks(input, |_, c| c == '#')
 }
 
 fn ver_asymmetry(pattern: &Grid<bool>, x: i32) -> usize {
@@ -40,6 +38,7 @@ fn hor_asymmetry(pattern: &Grid<bool>, y: i32) -> usize {
 
 fn part_(target_asymmetry: usize, input: &str) -> i32 {
     parse(input)
+        .into_iter()
         .map(|pattern| {
             for x in 1..pattern.size.x {
                 if ver_asymmetry(&pattern, x) == target_asymmetry {
@@ -1,6 +1,7 @@
-use std::collections::HashMap;
-
-use crate::grid::{Grid, Vector, E, N, S, W};
+use crate::{
+    cycle,
+    grid::{Grid, Vector, E, N, S, W},
+};
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 enum Tile {
@@ -61,30 +62,17 @@ pub fn part1(input: &str) -> i32 {
 }
 
 pub fn part2(input: &str) -> i32 {
-    fn spin(platform: &mut Grid<Tile>) {
+    fn spin(platform: &Grid<Tile>) -> Grid<Tile> {
+        let mut platform = platform.clone();
         for dir in [N, W, S, E] {
-            tilt(platform, dir);
-        }
-    }
-
-    fn find_cycle(platform: &mut Grid<Tile>) -> (usize, usize) {
-        let mut seen = HashMap::new();
-        for spins in 0.. {
-            if let Some(start) = seen.get(platform) {
-                return (spins, spins - start);
-            }
-            seen.insert(platform.clone(), spins);
-            spin(platform);
+            tilt(&mut platform, dir);
         }
-        unreachable!()
+        platform
     }
 
-    let mut platform = parse(input);
-    let (spins, period) = find_cycle(&mut platform);
-    for _ in 0..(1_000_000_000 - spins) % period {
-        spin(&mut platform);
-    }
-    score(&platform)
+    let platform = parse(input);
+    let cycle = cycle::hashed(platform.clone(), spin);
+    score(&cycle.state_at(platform, spin, 1_000_000_000))
 }
 
 pub fn tests() {
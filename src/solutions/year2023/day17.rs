@@ -1,6 +1,7 @@
 use crate::{
     grid::{Grid, Turn, Vector, E, LEFT, NW, RIGHT, Z},
-    search,
+    render::Cell,
+    search::{self, Stats},
 };
 
 #[derive(Clone)]
@@ -12,6 +13,13 @@ struct Crucible {
 }
 
 impl Crucible {
+    const START: Self = Self {
+        pos: Z,
+        dir: E,
+        straight_len: 0,
+        heat_loss: 0,
+    };
+
     fn step(&self, city: &Grid<u32>, turn: Option<Turn>) -> Option<Self> {
         let mut crucible = self.clone();
         if let Some(turn) = turn {
@@ -24,6 +32,32 @@ impl Crucible {
         crucible.heat_loss += city.get(crucible.pos)?;
         Some(crucible)
     }
+
+    fn moves(
+        &self,
+        city: &Grid<u32>,
+        min_straight_len: u8,
+        max_straight_len: u8,
+        push: &mut dyn FnMut(Self),
+    ) {
+        if self.straight_len >= min_straight_len {
+            if let Some(crucible) = self.step(city, Some(LEFT)) {
+                push(crucible);
+            }
+            if let Some(crucible) = self.step(city, Some(RIGHT)) {
+                push(crucible);
+            }
+        }
+        if self.straight_len < max_straight_len {
+            if let Some(crucible) = self.step(city, None) {
+                push(crucible);
+            }
+        }
+    }
+
+    fn key(&self) -> (Vector, Vector, u8) {
+        (self.pos, self.dir, self.straight_len)
+    }
 }
 
 fn part_(min_straight_len: u8, max_straight_len: u8, input: &str) -> u32 {
@@ -31,30 +65,9 @@ fn part_(min_straight_len: u8, max_straight_len: u8, input: &str) -> u32 {
     let target = city.size + NW;
 
     search::dijkstra(
-        Crucible {
-            pos: Z,
-            dir: E,
-            straight_len: 0,
-            heat_loss: 0,
-        },
-        move |crucible, push| {
-            if crucible.straight_len >= min_straight_len {
-                if let Some(crucible) = crucible.step(&city, Some(LEFT)) {
-                    push(crucible);
-                }
-                if let Some(crucible) = crucible.step(&city, Some(RIGHT)) {
-                    push(crucible);
-                }
-            }
-            if crucible.straight_len < max_straight_len {
-                if let Some(crucible) = crucible.step(&city, None) {
-                    push(crucible);
-                }
-            }
-        },
-        search::hash_filter(|crucible: &Crucible| {
-            (crucible.pos, crucible.dir, crucible.straight_len)
-        }),
+        Crucible::START,
+        |crucible, push| crucible.moves(&city, min_straight_len, max_straight_len, push),
+        search::hash_filter(Crucible::key),
         |crucible| crucible.heat_loss,
     )
     .find(|crucible| crucible.pos == target && crucible.straight_len >= min_straight_len)
@@ -62,6 +75,43 @@ fn part_(min_straight_len: u8, max_straight_len: u8, input: &str) -> u32 {
     .heat_loss
 }
 
+/// Run part 2 with and without a manhattan distance heuristic, returning the least heat loss, the
+/// search stats, and a frame showing the order in which blocks were expanded for each.
+pub fn trace(input: &str) -> Vec<(&'static str, u32, Stats, Grid<Cell>)> {
+    let city = Grid::parse(input, |_, c| c.to_digit(10).unwrap());
+    let target = city.size + NW;
+    let mut res = Vec::new();
+    for (name, use_heuristic) in [("dijkstra", false), ("manhattan", true)] {
+        let stats = Stats::new();
+        let mut order = Vec::new();
+        let heat_loss = search::a_star(
+            Crucible::START,
+            stats.adjacent(|crucible: &Crucible, push: &mut dyn FnMut(Crucible)| {
+                crucible.moves(&city, 4, 10, push);
+            }),
+            stats.filter(search::hash_filter(Crucible::key)),
+            |crucible| crucible.heat_loss,
+            // Every block loses at least 1 heat, so the manhattan distance is a lower bound.
+            |crucible| {
+                if use_heuristic {
+                    (target - crucible.pos).abs().sum().unsigned_abs()
+                } else {
+                    0
+                }
+            },
+        )
+        .inspect(|crucible| order.push(crucible.pos))
+        .find(|crucible| crucible.pos == target && crucible.straight_len >= 4)
+        .unwrap()
+        .heat_loss;
+        let mut frame =
+            Grid::from(&city.map(|_, &heat_loss| char::from_digit(heat_loss, 10).unwrap()));
+        frame.trace(order);
+        res.push((name, heat_loss, stats, frame));
+    }
+    res
+}
+
 pub fn part1(input: &str) -> u32 {
     part_(0, 3, input)
 }
@@ -98,4 +148,8 @@ pub fn tests() {
     assert_eq!(part1(&example0), 102);
     assert_eq!(part2(&example0), 94);
     assert_eq!(part2(&example1), 71);
+
+    for (_, heat_loss, _, _) in trace(&example0) {
+        assert_eq!(heat_loss, 94);
+    }
 }
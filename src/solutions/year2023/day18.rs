@@ -2,7 +2,10 @@ use std::sync::LazyLock;
 
 use regex::Regex;
 
-use crate::grid::{IntoVector, Vector, E, N, S, W, Z};
+use crate::{
+    geometry,
+    grid::{IntoVector, Vector, E, N, S, W, Z},
+};
 
 static RE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"([UDLR]) (\d+) \(#([0-9a-f]{5})([0-3])\)").unwrap());
@@ -32,25 +35,13 @@ fn parse2(input: &str) -> impl Iterator<Item = (Vector, i32)> + '_ {
 }
 
 fn part_(input: impl Iterator<Item = (Vector, i32)>) -> i64 {
-    let mut pos = Z;
-    let mut boundary = vec![pos];
-    let mut boundary_len = 0;
-    for (dir, side_len) in input {
-        pos += dir * side_len;
-        boundary.push(pos);
-        boundary_len += i64::from(side_len);
-    }
-
-    // https://en.wikipedia.org/wiki/Shoelace_formula
-    let mut area = 0;
-    for i in 0..boundary.len() - 1 {
-        area += i64::from(boundary[i].y + boundary[i + 1].y)
-            * i64::from(boundary[i].x - boundary[i + 1].x);
-    }
-    area /= 2;
-
-    // https://en.wikipedia.org/wiki/Pick%27s_theorem
-    area + boundary_len / 2 + 1
+    let vertices: Vec<Vector> = input
+        .scan(Z, |pos, (dir, side_len)| {
+            *pos += dir * side_len;
+            Some(*pos)
+        })
+        .collect();
+    geometry::interior_points(&vertices) + geometry::boundary_points(&vertices)
 }
 
 pub fn part1(input: &str) -> i64 {
@@ -1,8 +1,6 @@
-use std::collections::{HashMap, HashSet};
-
 use crate::{
+    graph::Graph,
     grid::{Adjacent, Grid, IntoVector, Vector},
-    search,
 };
 
 #[derive(Clone, Copy, PartialEq)]
@@ -21,75 +19,38 @@ fn parse(input: &str) -> Grid<Tile> {
     })
 }
 
-fn reachable(map: &Grid<Tile>, nodes: &HashSet<Vector>, start: Vector) -> HashMap<Vector, usize> {
-    search::breadth_first(
-        (start, 0),
-        |&(pos, steps), push| {
-            if nodes.contains(&pos) && pos != start {
-                // stop
-            } else if let Tile::Slope(dir) = map[pos] {
-                push((pos + dir, steps + 1));
-            } else {
-                pos.adjacent4()
-                    .filter(|&v| map.get(v).is_some_and(|&tile| tile != Tile::Forest))
-                    .for_each(|v| push((v, steps + 1)));
-            }
+fn graph(map: &Grid<Tile>, start: Vector, end: Vector) -> Graph<Vector, u32> {
+    let open = |v| map.get(v).is_some_and(|&tile| tile != Tile::Forest);
+    let junctions = map
+        .keys()
+        .filter(|&pos| open(pos) && pos.adjacent4().filter(|&v| open(v)).count() >= 3);
+    Graph::from_maze(
+        map,
+        |from, to| {
+            open(to)
+                && match map[from] {
+                    Tile::Slope(dir) => from + dir == to,
+                    _ => true,
+                }
         },
-        search::hash_filter(|&(pos, _)| pos),
+        [start, end].into_iter().chain(junctions),
     )
-    .filter(|&(pos, _)| nodes.contains(&pos) && pos != start)
-    .collect()
 }
 
-fn graph(map: &Grid<Tile>, start: Vector, end: Vector) -> HashMap<Vector, HashMap<Vector, usize>> {
-    let mut nodes = HashSet::from([start, end]);
-    nodes.extend(map.keys().filter(|&pos| {
-        pos.adjacent4()
-            .filter(|&v| map.get(v).is_some_and(|&tile| tile != Tile::Forest))
-            .count()
-            >= 3
-    }));
-    nodes
-        .iter()
-        .map(|&node| (node, reachable(map, &nodes, node)))
-        .collect()
-}
-
-fn steps(graph: &HashMap<Vector, HashMap<Vector, usize>>, path: &[Vector]) -> usize {
-    path.windows(2).map(|hop| graph[&hop[0]][&hop[1]]).sum()
-}
-
-fn part_(map: &Grid<Tile>) -> usize {
+fn part_(map: &Grid<Tile>) -> u32 {
     let start = Vector::new(1, 0);
     let end = map.size - Vector::new(2, 1);
     let graph = graph(map, start, end);
-    search::breadth_first(
-        vec![start],
-        |path, push| {
-            let pos = *path.last().unwrap();
-            if pos != end {
-                for &p in graph[&pos].keys() {
-                    if !path.contains(&p) {
-                        let mut path = path.clone();
-                        path.push(p);
-                        push(path);
-                    }
-                }
-            }
-        },
-        search::no_filter,
-    )
-    .filter(|path| *path.last().unwrap() == end)
-    .map(|path| steps(&graph, &path))
-    .max()
-    .unwrap()
+    graph
+        .longest_path(graph.id(&start).unwrap(), graph.id(&end).unwrap())
+        .unwrap()
 }
 
-pub fn part1(input: &str) -> usize {
+pub fn part1(input: &str) -> u32 {
     part_(&parse(input))
 }
 
-pub fn part2(input: &str) -> usize {
+pub fn part2(input: &str) -> u32 {
     let mut map = parse(input);
     for pos in map.keys() {
         if let Tile::Slope(_) = map[pos] {
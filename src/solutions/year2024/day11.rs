@@ -1,29 +1,28 @@
-use std::collections::HashMap;
+use crate::memo::Memo;
 
-use crate::freqs::Freqs;
-
-fn blink(stones: &HashMap<usize, usize>) -> HashMap<usize, usize> {
-    let mut next = HashMap::new();
-    for (&stone, &freq) in stones {
+// The number of stones a single stone becomes after blinking the given number of times.
+fn count(memo: &mut Memo<(usize, usize), usize>, stone: usize, blinks: usize) -> usize {
+    memo.get((stone, blinks), |memo| {
         let s = stone.to_string();
-        if stone == 0 {
-            *next.entry(1).or_default() += freq;
-        } else if s.len() % 2 == 0 {
-            *next.entry(s[0..s.len() / 2].parse().unwrap()).or_default() += freq;
-            *next.entry(s[s.len() / 2..].parse().unwrap()).or_default() += freq;
+        if blinks == 0 {
+            1
+        } else if stone == 0 {
+            count(memo, 1, blinks - 1)
+        } else if s.len().is_multiple_of(2) {
+            count(memo, s[0..s.len() / 2].parse().unwrap(), blinks - 1)
+                + count(memo, s[s.len() / 2..].parse().unwrap(), blinks - 1)
         } else {
-            *next.entry(stone * 2024).or_default() += freq;
+            count(memo, stone * 2024, blinks - 1)
         }
-    }
-    next
+    })
 }
 
 fn part_(blinks: usize, input: &str) -> usize {
-    let mut stones = input.split_whitespace().map(|s| s.parse().unwrap()).freqs();
-    for _ in 0..blinks {
-        stones = blink(&stones);
-    }
-    stones.values().sum()
+    let mut memo = Memo::new();
+    input
+        .split_whitespace()
+        .map(|s| count(&mut memo, s.parse().unwrap(), blinks))
+        .sum()
 }
 
 pub fn part1(input: &str) -> usize {
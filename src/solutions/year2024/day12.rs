@@ -1,72 +1,24 @@
 use std::collections::HashSet;
 
 use crate::{
-    grid::{self, Adjacent, IntoVector, Vector},
-    search,
+    geometry,
+    grid::{Grid, Vector},
 };
 
-// Represent a crop square by a pair of position + crop, and the whole garden by the set of crop
-// squares.
-fn parse(input: &str) -> HashSet<(Vector, char)> {
-    grid::scan(input).collect()
-}
-
-// Represent the edge of a square by a pair indicating the position of the square and the compass
-// direction of the edge (N, E, S, or W). Then the perimeter is a set of edges.
-fn perimeter(region: &HashSet<(Vector, char)>) -> HashSet<(Vector, char)> {
-    region
+fn part_(input: &str, price: fn(&HashSet<Vector>) -> usize) -> usize {
+    geometry::regions(&Grid::parse(input, |_, c| c))
         .iter()
-        .flat_map(|&(v, crop)| {
-            "NESW"
-                .chars()
-                .map(move |c| (v, c))
-                .filter(move |&(u, c)| !region.contains(&(u + c.into_vector(), crop)))
-        })
-        .collect()
-}
-
-// Break a garden into subsets such that for each subset:
-//
-// - The positions form a single connected region, and
-// - The chars (either a crop or a direction) are all the same.
-//
-// This works both for finding the initial breakdown of crop regions, and for finding the breakdown
-// of a perimeter into edges!
-fn regions(garden: &HashSet<(Vector, char)>) -> Vec<HashSet<(Vector, char)>> {
-    let mut res: Vec<HashSet<(Vector, char)>> = Vec::new();
-    for &(v, c) in garden {
-        if res.iter().any(|r| r.contains(&(v, c))) {
-            continue;
-        }
-        res.push(
-            search::breadth_first(
-                v,
-                |&v, push| {
-                    v.adjacent4()
-                        .filter(|&u| garden.contains(&(u, c)))
-                        .for_each(push);
-                },
-                search::id_filter(),
-            )
-            .map(|v| (v, c))
-            .collect(),
-        );
-    }
-    res
+        .map(|region| region.len() * price(region))
+        .sum()
 }
 
 pub fn part1(input: &str) -> usize {
-    regions(&parse(input))
-        .iter()
-        .map(|region| region.len() * perimeter(region).len())
-        .sum()
+    part_(input, geometry::perimeter)
 }
 
 pub fn part2(input: &str) -> usize {
-    regions(&parse(input))
-        .iter()
-        .map(|region| region.len() * regions(&perimeter(region)).len())
-        .sum()
+    // A region has as many sides as it has corners.
+    part_(input, geometry::corners)
 }
 
 pub fn tests() {
@@ -97,4 +49,8 @@ pub fn tests() {
     assert_eq!(part2(&example2), 1206);
     assert_eq!(part2(&example3), 236);
     assert_eq!(part2(&example4), 368);
+
+    let grid = Grid::parse(&example0, |_, c| c);
+    assert_eq!(geometry::region(&grid, Vector::new(0, 0)).len(), 4);
+    assert!(geometry::region(&grid, Vector::new(-1, 0)).is_empty());
 }
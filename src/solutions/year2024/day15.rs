@@ -1,5 +1,8 @@
+use std::time::Duration;
+
 use crate::{
     grid::{Grid, IntoVector, Vector, E, W},
+    render::{Palette, Recorder, BLUE, GREY, RED, YELLOW},
     search,
 };
 
@@ -35,7 +38,8 @@ fn expand(state: &Grid<char>) -> Grid<char> {
     res
 }
 
-fn tick(state: &mut Grid<char>, dir: Vector) {
+// Returns where everything that moved ended up, which is nowhere if the robot was blocked.
+fn tick(state: &mut Grid<char>, dir: Vector) -> Vec<Vector> {
     let objects: Vec<Vector> = search::breadth_first(
         state.keys().find(|&v| state[v] == '@').unwrap(),
         |&v, push| match state[v] {
@@ -66,6 +70,9 @@ fn tick(state: &mut Grid<char>, dir: Vector) {
             next[v + dir] = state[v];
         }
         *state = next;
+        objects.iter().map(|&v| v + dir).collect()
+    } else {
+        Vec::new()
     }
 }
 
@@ -84,6 +91,23 @@ fn part_(mut state: Grid<char>, dirs: impl Iterator<Item = Vector>) -> i32 {
     score(&state)
 }
 
+/// Record the robot shoving boxes around the widened warehouse of part 2, with whatever moved on
+/// each step highlighted.
+pub fn record(input: &str) -> Recorder {
+    let palette = Palette::new([('#', GREY), ('[', YELLOW), (']', YELLOW), ('@', RED)]);
+    let (state, dirs) = parse(input);
+    let mut state = expand(&state);
+    let mut recorder = Recorder::new();
+    recorder.record(&palette.paint(&state), Duration::ZERO);
+    for dir in dirs {
+        let moved = tick(&mut state, dir);
+        let mut frame = palette.paint(&state);
+        frame.highlight(moved, BLUE);
+        recorder.record(&frame, Duration::from_millis(50));
+    }
+    recorder
+}
+
 pub fn part1(input: &str) -> i32 {
     let (state, dirs) = parse(input);
     part_(state, dirs)
@@ -135,4 +159,5 @@ pub fn tests() {
     assert_eq!(part1(&small_example), 2028);
     assert_eq!(part1(&big_example), 10092);
     assert_eq!(part2(&big_example), 9021);
+    assert_eq!(record(&small_example).len(), 16);
 }
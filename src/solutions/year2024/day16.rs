@@ -1,7 +1,5 @@
-use std::collections::HashMap;
-
 use crate::{
-    grid::{Grid, Turn, Vector, E, LEFT, RIGHT},
+    grid::{Grid, Vector, E, LEFT, N, RIGHT, S, W},
     search,
     uniq::Uniq,
 };
@@ -11,88 +9,116 @@ struct State {
     pos: Vector,
     dir: Vector,
     score: usize,
-    path: Vec<Vector>,
 }
 
-impl State {
-    fn step(&mut self) {
-        self.pos += self.dir;
-        self.score += 1;
-        self.path.push(self.pos);
-    }
-
-    fn run(&self, maze: &Grid<char>) -> Self {
-        let mut state = self.clone();
-        state.step();
-        while maze[state.pos + state.dir] != '#'
-            && maze[state.pos + LEFT * state.dir] == '#'
-            && maze[state.pos + RIGHT * state.dir] == '#'
-        {
-            state.step();
+fn adjacent(maze: &Grid<char>, state: &State, push: &mut dyn FnMut(State)) {
+    if maze[state.pos] != 'E' {
+        if maze[state.pos + state.dir] != '#' {
+            push(State {
+                pos: state.pos + state.dir,
+                score: state.score + 1,
+                ..*state
+            });
+        }
+        for turn in [LEFT, RIGHT] {
+            if maze[state.pos + turn * state.dir] != '#' {
+                push(State {
+                    dir: turn * state.dir,
+                    score: state.score + 1000,
+                    ..*state
+                });
+            }
         }
-        state
-    }
-
-    fn turn(&self, turn: Turn) -> Self {
-        let mut state = self.clone();
-        state.dir = turn * state.dir;
-        state.score += 1000;
-        state
     }
 }
 
-fn search(maze: &Grid<char>) -> impl Iterator<Item = State> + '_ {
+// Returns the lowest score, and the number of tiles on any path with that score.
+fn part_(input: &str) -> (usize, usize) {
+    let maze = Grid::parse(input, |_, c| c);
     let start = maze.keys().find(|&v| maze[v] == 'S').unwrap();
-    let mut lowest_score = HashMap::new();
-    search::dijkstra(
+    let mut search = search::dijkstra_paths(
         State {
             pos: start,
             dir: E,
             score: 0,
-            path: vec![start],
-        },
-        |state, push| {
-            if maze[state.pos] != 'E' {
-                if maze[state.pos + state.dir] != '#' {
-                    push(state.run(maze));
-                }
-                for turn in [LEFT, RIGHT] {
-                    if maze[state.pos + turn * state.dir] != '#' {
-                        push(state.turn(turn).run(maze));
-                    }
-                }
-            }
-        },
-        // Track the lowest score that we see each (pos, dir) and only consider optimal branches.
-        move |state| {
-            if let Some(&score) = lowest_score.get(&(state.pos, state.dir)) {
-                state.score == score
-            } else {
-                lowest_score.insert((state.pos, state.dir), state.score);
-                true
-            }
         },
+        |state, push| adjacent(&maze, state, push),
+        |state| (state.pos, state.dir),
         |state| state.score,
-    )
+    );
+
+    let end = search.find(|state| maze[state.pos] == 'E').unwrap();
+
+    // Every state with a lower score has been expanded by now, so we know all the best ways to
+    // reach the end, including facing in a different direction.
+    let tiles = [N, E, S, W]
+        .into_iter()
+        .map(|dir| (end.pos, dir))
+        .filter(|key| search.cost(key) == Some(&end.score))
+        .flat_map(|key| search.on_best_paths(&key))
+        .map(|(pos, _)| pos)
+        .uniq()
+        .count();
+
+    (end.score, tiles)
 }
 
 pub fn part1(input: &str) -> usize {
-    let maze = Grid::parse(input, |_, c| c);
-    let res = search(&maze)
-        .find(|state| maze[state.pos] == 'E')
-        .unwrap()
-        .score;
-    res
+    part_(input).0
 }
 
 pub fn part2(input: &str) -> usize {
-    let min_score = part1(input);
-    let maze = Grid::parse(input, |_, c| c);
-    search(&maze)
-        .filter(|state| state.score == min_score && maze[state.pos] == 'E')
-        .flat_map(|state| state.path)
+    part_(input).1
+}
+
+// Like `part_`, but searching with A*, which needs to carry on past the end until every state which
+// might still be on a best path has been expanded.
+fn a_star(maze: &Grid<char>) -> (usize, usize) {
+    let start = maze.keys().find(|&v| maze[v] == 'S').unwrap();
+    let end = maze.keys().find(|&v| maze[v] == 'E').unwrap();
+    let heuristic = |state: &State| usize::try_from((end - state.pos).abs().sum()).unwrap();
+    let mut search = search::a_star_paths(
+        State {
+            pos: start,
+            dir: E,
+            score: 0,
+        },
+        |state, push| adjacent(maze, state, push),
+        |state| (state.pos, state.dir),
+        |state| state.score,
+        heuristic,
+    );
+    let score = search.find(|state| state.pos == end).unwrap().score;
+    search
+        .by_ref()
+        .take_while(|state| state.score + heuristic(state) <= score)
+        .for_each(drop);
+    let tiles = [N, E, S, W]
+        .into_iter()
+        .filter(|&dir| search.cost(&(end, dir)) == Some(&score))
+        .flat_map(|dir| search.on_best_paths(&(end, dir)))
+        .map(|(pos, _)| pos)
         .uniq()
-        .count()
+        .count();
+    (score, tiles)
+}
+
+// The fewest steps from start to end, ignoring the cost of turning, as a list of positions.
+fn shortest_walk(maze: &Grid<char>) -> Vec<Vector> {
+    let start = maze.keys().find(|&v| maze[v] == 'S').unwrap();
+    let mut search = search::breadth_first_paths(
+        start,
+        |&pos, push| {
+            for dir in [N, E, S, W] {
+                if maze[pos + dir] != '#' {
+                    push(pos + dir);
+                }
+            }
+        },
+        |&pos| pos,
+    );
+    let end = search.find(|&pos| maze[pos] == 'E').unwrap();
+    search.path(&end)
 }
 
 pub fn tests() {
@@ -138,4 +164,15 @@ pub fn tests() {
     assert_eq!(part1(&example1), 11048);
     assert_eq!(part2(&example0), 45);
     assert_eq!(part2(&example1), 64);
+
+    for (example, score, tiles, steps) in [(&example0, 7036, 45, 28), (&example1, 11048, 64, 40)] {
+        let maze = Grid::parse(example, |_, c| c);
+        assert_eq!(a_star(&maze), (score, tiles));
+        let walk = shortest_walk(&maze);
+        assert_eq!(walk.len(), steps + 1);
+        assert_eq!(maze[walk[0]], 'S');
+        assert_eq!(maze[*walk.last().unwrap()], 'E');
+        assert!(walk.windows(2).all(|w| (w[1] - w[0]).abs().sum() == 1));
+        assert!(walk.iter().all(|&pos| maze[pos] != '#'));
+    }
 }
@@ -1,50 +1,40 @@
-use std::collections::HashMap;
+use crate::memo::{Memo, VecCache};
 
 fn parse(input: &str) -> (Vec<&str>, impl Iterator<Item = &str> + '_) {
     let (towels, designs) = input.trim().split_once("\n\n").unwrap();
     (towels.split(", ").collect(), designs.split_whitespace())
 }
 
-fn arrangements<'a>(
-    cache: &mut HashMap<&'a str, usize>,
-    towels: &[&'a str],
-    design: &'a str,
+// Only ever called on suffixes of the same design, so the length is enough to identify them.
+fn arrangements(
+    memo: &mut Memo<usize, usize, VecCache<usize>>,
+    towels: &[&str],
+    design: &str,
 ) -> usize {
-    fn go<'a>(cache: &mut HashMap<&'a str, usize>, towels: &[&'a str], design: &'a str) -> usize {
+    memo.get(design.len(), |memo| {
         if design.is_empty() {
             1
         } else {
             towels
                 .iter()
                 .filter(|&towel| design.starts_with(towel))
-                .map(|towel| arrangements(cache, towels, &design[towel.len()..]))
+                .map(|towel| arrangements(memo, towels, &design[towel.len()..]))
                 .sum()
         }
-    }
+    })
+}
 
-    if let Some(&res) = cache.get(design) {
-        res
-    } else {
-        let res = go(cache, towels, design);
-        cache.insert(design, res);
-        res
-    }
+fn part_(input: &str) -> impl Iterator<Item = usize> + '_ {
+    let (towels, designs) = parse(input);
+    designs.map(move |design| arrangements(&mut Memo::with_cache(VecCache::new()), &towels, design))
 }
 
 pub fn part1(input: &str) -> usize {
-    let mut cache = HashMap::new();
-    let (towels, designs) = parse(input);
-    designs
-        .filter(|design| arrangements(&mut cache, &towels, design) > 0)
-        .count()
+    part_(input).filter(|&n| n > 0).count()
 }
 
 pub fn part2(input: &str) -> usize {
-    let mut cache = HashMap::new();
-    let (towels, designs) = parse(input);
-    designs
-        .map(|design| arrangements(&mut cache, &towels, design))
-        .sum()
+    part_(input).sum()
 }
 
 pub fn tests() {
@@ -7,32 +7,20 @@ use crate::{
 };
 
 fn parse(input: &str) -> (Grid<bool>, Vector) {
-    let mut start = None;
-    let grid = Grid::parse(input, |v, c| match c {
-        '.' | 'E' => true,
+    let (grid, [start]) = Grid::parse_find(input, ['S'], |_, c| match c {
+        '.' | 'S' | 'E' => true,
         '#' => false,
-        'S' => {
-            start = Some(v);
-            true
-        }
         _ => unreachable!(),
     });
-    (grid, start.unwrap())
+    (grid, start)
 }
 
 fn run(grid: &Grid<bool>, start: Vector) -> HashMap<Vector, i32> {
-    search::breadth_first(
-        (start, 0),
-        |&(pos, picoseconds), push| {
-            pos.adjacent4()
-                .filter(|&a| grid.get(a).is_some_and(|&t| t))
-                .for_each(|a| {
-                    push((a, picoseconds + 1));
-                });
-        },
-        search::hash_filter(|&(pos, _)| pos),
-    )
-    .collect()
+    search::distances(start, |&pos, push| {
+        pos.adjacent4()
+            .filter(|&a| grid.get(a).is_some_and(|&t| t))
+            .for_each(|a| push(a, 1));
+    })
 }
 
 fn cheats(track: &HashMap<Vector, i32>, max_duration: i32) -> impl Iterator<Item = i32> + '_ {
@@ -1,77 +1,35 @@
-use std::{
-    cmp::Reverse,
-    collections::{HashMap, HashSet},
-};
+use crate::{graph::Graph, uniq::Uniq};
 
-use crate::uniq::Uniq;
-
-fn parse(input: &str) -> HashMap<&str, HashSet<&str>> {
-    let mut res: HashMap<&str, HashSet<&str>> = HashMap::new();
+fn parse(input: &str) -> Graph<&str, ()> {
+    let mut res = Graph::undirected();
     for edge in input.split_whitespace() {
         let (a, b) = edge.split_once('-').unwrap();
-        res.entry(a).or_default().insert(b);
-        res.entry(b).or_default().insert(a);
+        res.add_edge(a, b, ());
     }
     res
 }
 
-fn maximal_cliques<'a>(g: &HashMap<&'a str, HashSet<&'a str>>) -> Vec<HashSet<&'a str>> {
-    // https://en.wikipedia.org/wiki/Bron%E2%80%93Kerbosch_algorithm
-    fn bron_kerbosch<'a>(
-        g: &HashMap<&'a str, HashSet<&'a str>>,
-        r: HashSet<&'a str>,
-        mut p: HashSet<&'a str>,
-        mut x: HashSet<&'a str>,
-    ) -> Vec<HashSet<&'a str>> {
-        if p.is_empty() && x.is_empty() {
-            vec![r]
-        } else {
-            let mut res = Vec::new();
-            while let Some(&v) = p.iter().next() {
-                res.extend(bron_kerbosch(
-                    g,
-                    r.union(&HashSet::from([v])).copied().collect(),
-                    p.intersection(&g[v]).copied().collect(),
-                    x.intersection(&g[v]).copied().collect(),
-                ));
-                p.remove(v);
-                x.insert(v);
-            }
-            res
-        }
-    }
-    bron_kerbosch(
-        g,
-        HashSet::new(),
-        g.keys().copied().collect(),
-        HashSet::new(),
-    )
-}
-
 pub fn part1(input: &str) -> usize {
-    let g = parse(input);
-    g.keys()
-        .filter(|&a| a.starts_with('t'))
-        .flat_map(|&a| {
-            g[a].iter()
-                .filter(|&b| g[b].contains(a))
-                .map(move |&b| (a, b))
-        })
+    let g = &parse(input);
+    g.ids()
+        .filter(|&a| g[a].starts_with('t'))
+        .flat_map(|a| g.neighbors(a).map(move |(b, ())| (a, b)))
         .flat_map(|(a, b)| {
-            g[a].intersection(&g[b]).map(move |&c| {
-                let mut tri = [a, b, c];
-                tri.sort_unstable();
-                tri
-            })
+            g.neighbors(b)
+                .filter(move |&(c, ())| g.edge(a, c).is_some())
+                .map(move |(c, ())| {
+                    let mut tri = [a, b, c];
+                    tri.sort_unstable();
+                    tri
+                })
         })
         .uniq()
         .count()
 }
 
 pub fn part2(input: &str) -> String {
-    let mut cliques = maximal_cliques(&parse(input));
-    cliques.sort_unstable_by_key(|c| Reverse(c.len()));
-    let mut clique: Vec<&str> = cliques[0].iter().copied().collect();
+    let g = parse(input);
+    let mut clique: Vec<&str> = g.max_clique().into_iter().map(|a| g[a]).collect();
     clique.sort_unstable();
     clique.join(",")
 }
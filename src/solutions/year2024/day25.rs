@@ -1,10 +1,7 @@
 use crate::{combinatorics, grid::Grid};
 
 fn parse(input: &str) -> Vec<Grid<bool>> {
-    input
-        .split("\n\n")
-        .map(|schematic| Grid::parse(schematic, |_, c| c == '#'))
-        .collect()
+    Grid::parse_blocks(input, |_, c| c == '#')
 }
 
 pub fn part1(input: &str) -> usize {